@@ -0,0 +1,429 @@
+use reqwest::{header::CONTENT_RANGE, Method, RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        ApprovalStatus, Assignment, AttestationKPIs, AttestationResponse, CTypeRequest,
+        ChallengeData, ChallengeResponse, Comment, Credential, EncryptedMessage, ErrorResponse,
+        KpiFilter, NewAssignment, NewComment, Query, Rejection, Review, Revision,
+        VerificationRequest,
+    },
+    routes,
+};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("API error {status}: {}", error.message)]
+    Api {
+        status: StatusCode,
+        error: ErrorResponse,
+    },
+    /// The attestation has to be paid first. Contains the encrypted `request-payment` message.
+    #[error("Payment required")]
+    PaymentRequired(EncryptedMessage),
+}
+
+/// A page of a list and the total number of items.
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: Option<i64>,
+}
+
+/// Client of the REST API of the attester service. Apart from the endpoints, all routes require
+/// the JWT issued by OpenDID, which is set with [`AttesterClient::with_token`].
+#[derive(Clone, Debug)]
+pub struct AttesterClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl AttesterClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        AttesterClient {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, ClientError> {
+        let response = request.send().await?;
+        parse_response(response).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        Self::send(self.request(Method::GET, path)).await
+    }
+
+    async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ClientError> {
+        Self::send(self.request(Method::POST, path).json(body)).await
+    }
+
+    async fn put<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        Self::send(self.request(Method::PUT, path)).await
+    }
+
+    async fn put_json<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ClientError> {
+        Self::send(self.request(Method::PUT, path).json(body)).await
+    }
+
+    // Attestation requests
+
+    pub async fn get_attestation_request(
+        &self,
+        id: &Uuid,
+    ) -> Result<AttestationResponse, ClientError> {
+        self.get(&format!("{}/{id}", routes::ATTESTATION_REQUEST))
+            .await
+    }
+
+    /// Lists the attestation requests. Users only see their own requests.
+    pub async fn get_attestation_requests(
+        &self,
+        query: &Query,
+    ) -> Result<Page<AttestationResponse>, ClientError> {
+        let response = self
+            .request(Method::GET, routes::ATTESTATION_REQUEST)
+            .query(query)
+            .send()
+            .await?;
+        let total = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let items = parse_response(response).await?;
+        Ok(Page { items, total })
+    }
+
+    pub async fn create_attestation_request(
+        &self,
+        credential: &Credential,
+    ) -> Result<AttestationResponse, ClientError> {
+        self.post(routes::ATTESTATION_REQUEST, credential).await
+    }
+
+    pub async fn delete_attestation_request(&self, id: &Uuid) -> Result<(), ClientError> {
+        let request = self.request(
+            Method::DELETE,
+            &format!("{}/{id}", routes::ATTESTATION_REQUEST),
+        );
+        Self::send::<String>(request).await.map(|_| ())
+    }
+
+    pub async fn update_credential(
+        &self,
+        id: &Uuid,
+        credential: &Credential,
+    ) -> Result<AttestationResponse, ClientError> {
+        self.put_json(
+            &format!("{}/{id}/credential", routes::ATTESTATION_REQUEST),
+            credential,
+        )
+        .await
+    }
+
+    /// Approves the attestation request. The attestation is created on chain once the required
+    /// approvals are reached.
+    pub async fn approve_attestation_request(
+        &self,
+        id: &Uuid,
+    ) -> Result<ApprovalStatus, ClientError> {
+        self.put(&format!("{}/{id}/approve", routes::ATTESTATION_REQUEST))
+            .await
+    }
+
+    /// Approves the attestation request, which is attested once the claimer paid for it.
+    pub async fn mark_approve_attestation_request(
+        &self,
+        id: &Uuid,
+    ) -> Result<ApprovalStatus, ClientError> {
+        self.put(&format!(
+            "{}/{id}/mark_approve",
+            routes::ATTESTATION_REQUEST
+        ))
+        .await
+    }
+
+    pub async fn reject_attestation_request(
+        &self,
+        id: &Uuid,
+        rejection: &Rejection,
+    ) -> Result<(), ClientError> {
+        self.put_json::<_, String>(
+            &format!("{}/{id}/reject", routes::ATTESTATION_REQUEST),
+            rejection,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn request_changes(
+        &self,
+        id: &Uuid,
+        comment: &NewComment,
+    ) -> Result<(), ClientError> {
+        self.put_json::<_, String>(
+            &format!("{}/{id}/request_changes", routes::ATTESTATION_REQUEST),
+            comment,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn revoke_attestation(&self, id: &Uuid) -> Result<(), ClientError> {
+        self.put::<String>(&format!("{}/{id}/revoke", routes::ATTESTATION_REQUEST))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn request_revocation(&self, id: &Uuid) -> Result<(), ClientError> {
+        self.put::<String>(&format!(
+            "{}/{id}/request_revocation",
+            routes::ATTESTATION_REQUEST
+        ))
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn get_revocation_requests(&self) -> Result<Vec<AttestationResponse>, ClientError> {
+        self.get(&format!(
+            "{}/revocation_requests",
+            routes::ATTESTATION_REQUEST
+        ))
+        .await
+    }
+
+    pub async fn get_revisions(&self, id: &Uuid) -> Result<Vec<Revision>, ClientError> {
+        self.get(&format!("{}/{id}/revisions", routes::ATTESTATION_REQUEST))
+            .await
+    }
+
+    pub async fn get_comments(&self, id: &Uuid) -> Result<Vec<Comment>, ClientError> {
+        self.get(&format!("{}/{id}/comments", routes::ATTESTATION_REQUEST))
+            .await
+    }
+
+    pub async fn post_comment(
+        &self,
+        id: &Uuid,
+        comment: &NewComment,
+    ) -> Result<Comment, ClientError> {
+        self.post(
+            &format!("{}/{id}/comments", routes::ATTESTATION_REQUEST),
+            comment,
+        )
+        .await
+    }
+
+    pub async fn assign_reviewer(
+        &self,
+        id: &Uuid,
+        assignment: &NewAssignment,
+    ) -> Result<Assignment, ClientError> {
+        self.put_json(
+            &format!("{}/{id}/reviewers", routes::ATTESTATION_REQUEST),
+            assignment,
+        )
+        .await
+    }
+
+    pub async fn get_reviewers(&self, id: &Uuid) -> Result<Vec<Assignment>, ClientError> {
+        self.get(&format!("{}/{id}/reviewers", routes::ATTESTATION_REQUEST))
+            .await
+    }
+
+    pub async fn get_reviews(&self, id: &Uuid) -> Result<Vec<Review>, ClientError> {
+        self.get(&format!("{}/{id}/reviews", routes::ATTESTATION_REQUEST))
+            .await
+    }
+
+    pub async fn get_kpis(&self, filter: &KpiFilter) -> Result<AttestationKPIs, ClientError> {
+        let request = self
+            .request(
+                Method::GET,
+                &format!("{}/metric/kpis", routes::ATTESTATION_REQUEST),
+            )
+            .query(filter);
+        Self::send(request).await
+    }
+
+    // Challenge
+
+    pub async fn get_challenge(&self) -> Result<ChallengeData, ClientError> {
+        self.get(routes::CHALLENGE).await
+    }
+
+    /// Sends the encrypted challenge and returns the ID of the wallet session.
+    pub async fn post_challenge_response(
+        &self,
+        challenge_response: &ChallengeResponse,
+    ) -> Result<Uuid, ClientError> {
+        self.post(routes::CHALLENGE, challenge_response).await
+    }
+
+    // Credential API
+
+    pub async fn send_terms(
+        &self,
+        session_id: &Uuid,
+        attestation_id: &Uuid,
+    ) -> Result<EncryptedMessage, ClientError> {
+        let request = self.request(
+            Method::POST,
+            &format!("{}/terms/{session_id}/{attestation_id}", routes::CREDENTIAL),
+        );
+        Self::send(request).await
+    }
+
+    /// Sends the `request-attestation` message of the wallet. Returns
+    /// [`ClientError::PaymentRequired`] if the attestation has to be paid first.
+    pub async fn request_attestation(
+        &self,
+        session_id: &Uuid,
+        attestation_id: &Uuid,
+        message: &EncryptedMessage,
+    ) -> Result<EncryptedMessage, ClientError> {
+        self.post(
+            &format!("{}/{session_id}/{attestation_id}", routes::CREDENTIAL),
+            message,
+        )
+        .await
+    }
+
+    pub async fn confirm_payment(
+        &self,
+        session_id: &Uuid,
+        attestation_id: &Uuid,
+        message: &EncryptedMessage,
+    ) -> Result<EncryptedMessage, ClientError> {
+        self.post(
+            &format!(
+                "{}/payment/{session_id}/{attestation_id}",
+                routes::CREDENTIAL
+            ),
+            message,
+        )
+        .await
+    }
+
+    pub async fn deliver_attestation(
+        &self,
+        session_id: &Uuid,
+        attestation_id: &Uuid,
+    ) -> Result<EncryptedMessage, ClientError> {
+        let request = self.request(
+            Method::POST,
+            &format!(
+                "{}/attestation/{session_id}/{attestation_id}",
+                routes::CREDENTIAL
+            ),
+        );
+        Self::send(request).await
+    }
+
+    pub async fn request_credential(
+        &self,
+        session_id: &Uuid,
+        ctypes: &[CTypeRequest],
+    ) -> Result<EncryptedMessage, ClientError> {
+        self.post(
+            &format!("{}/request-credential/{session_id}", routes::CREDENTIAL),
+            ctypes,
+        )
+        .await
+    }
+
+    pub async fn submit_credential(
+        &self,
+        session_id: &Uuid,
+        message: &EncryptedMessage,
+    ) -> Result<VerificationRequest, ClientError> {
+        self.post(
+            &format!("{}/submit-credential/{session_id}", routes::CREDENTIAL),
+            message,
+        )
+        .await
+    }
+
+    pub async fn get_verification(
+        &self,
+        verification_request_id: &Uuid,
+    ) -> Result<VerificationRequest, ClientError> {
+        self.get(&format!(
+            "{}/verification/{verification_request_id}",
+            routes::CREDENTIAL
+        ))
+        .await
+    }
+
+    // Endpoints
+
+    /// Returns the URLs of OpenDID and of the KILT node.
+    pub async fn get_endpoints(&self) -> Result<Vec<String>, ClientError> {
+        self.get(routes::ENDPOINTS).await
+    }
+}
+
+async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response.json().await?);
+    }
+
+    let body = response.text().await?;
+    if status == StatusCode::PAYMENT_REQUIRED {
+        if let Ok(message) = serde_json::from_str(&body) {
+            return Err(ClientError::PaymentRequired(message));
+        }
+    }
+
+    // Errors which are not returned by the handlers, e.g. of a proxy, have no JSON body.
+    let error = serde_json::from_str(&body).unwrap_or_else(|_| ErrorResponse {
+        code: status
+            .canonical_reason()
+            .unwrap_or("unknown")
+            .to_lowercase()
+            .replace(' ', "_"),
+        message: body,
+        details: None,
+        request_id: None,
+    });
+    Err(ClientError::Api { status, error })
+}
@@ -0,0 +1,190 @@
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use reqwest::StatusCode;
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{AttesterClient, ClientError};
+use crate::dto::{AttestationState, Query};
+
+const TOKEN: &str = "token";
+
+fn attestation_request(id: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "approved": false,
+        "revoked": false,
+        "marked_approve": false,
+        "created_at": "2024-01-01T00:00:00",
+        "deleted_at": null,
+        "approved_at": null,
+        "revoked_at": null,
+        "revocation_requested_at": null,
+        "rejected": false,
+        "rejected_at": null,
+        "rejected_by": null,
+        "rejection_reason": null,
+        "changes_requested": false,
+        "changes_requested_at": null,
+        "delegation_id": null,
+        "ctype_hash": "0x1234",
+        "credential": {},
+        "claimer": "did:kilt:claimer",
+        "tx_state": null,
+        "status": "Pending",
+    })
+}
+
+fn is_authorized(req: &HttpRequest) -> bool {
+    req.headers()
+        .get("Authorization")
+        .is_some_and(|value| value == format!("Bearer {TOKEN}").as_str())
+}
+
+async fn get_attestation(req: HttpRequest, id: web::Path<String>) -> HttpResponse {
+    if !is_authorized(&req) {
+        return HttpResponse::Unauthorized().json(json!({
+            "code": "unauthorized",
+            "message": "Missing token",
+            "details": null,
+            "requestId": "request",
+        }));
+    }
+    HttpResponse::Ok().json(attestation_request(&id))
+}
+
+async fn get_attestations(query: web::Query<Query>) -> HttpResponse {
+    if query.range.as_deref() != Some("[0,9]") || query.sort.is_some() {
+        return HttpResponse::BadRequest().finish();
+    }
+    let id = Uuid::new_v4().to_string();
+    HttpResponse::Ok()
+        .insert_header(("Content-Range", "42"))
+        .json([attestation_request(&id)])
+}
+
+async fn request_attestation() -> HttpResponse {
+    HttpResponse::PaymentRequired().json(json!({
+        "ciphertext": "0x1234",
+        "nonce": format!("0x{}", "00".repeat(24)),
+        "receiverKeyUri": "did:kilt:light:claimer#encryption",
+        "senderKeyUri": "did:kilt:attester#encryption",
+    }))
+}
+
+/// Starts a server with canned responses and returns a client for it.
+async fn start_server() -> AttesterClient {
+    let server = HttpServer::new(|| {
+        App::new()
+            .route(
+                "/api/v1/attestation_request",
+                web::get().to(get_attestations),
+            )
+            .route(
+                "/api/v1/attestation_request/{id}",
+                web::get().to(get_attestation),
+            )
+            .route(
+                "/api/v1/credential/{session}/{attestation_id}",
+                web::post().to(request_attestation),
+            )
+            .route(
+                "/api/v1/endpoints",
+                web::get().to(|| async { HttpResponse::Ok().json(["auth", "wss"]) }),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let address = server.addrs()[0];
+    tokio::spawn(server.run());
+
+    AttesterClient::new(format!("http://{address}/"))
+}
+
+#[actix_web::test]
+async fn test_get_attestation_request() {
+    // Arrange
+    let client = start_server().await.with_token(TOKEN);
+    let id = Uuid::new_v4();
+
+    // Act
+    let attestation = client.get_attestation_request(&id).await.unwrap();
+
+    // Assert
+    assert_eq!(attestation.id, id);
+    assert_eq!(attestation.status, AttestationState::Pending);
+}
+
+#[actix_web::test]
+async fn test_error_response() {
+    // Arrange
+    let client = start_server().await;
+
+    // Act
+    let result = client.get_attestation_request(&Uuid::new_v4()).await;
+
+    // Assert
+    match result {
+        Err(ClientError::Api { status, error }) => {
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(error.code, "unauthorized");
+            assert_eq!(error.request_id.as_deref(), Some("request"));
+        }
+        other => panic!("Expected API error, got {:?}", other.map(|a| a.id)),
+    }
+}
+
+#[actix_web::test]
+async fn test_get_attestation_requests_sends_query() {
+    // Arrange
+    let client = start_server().await;
+    let query = Query {
+        range: Some("[0,9]".to_string()),
+        ..Default::default()
+    };
+
+    // Act
+    let page = client.get_attestation_requests(&query).await.unwrap();
+
+    // Assert
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.total, Some(42));
+}
+
+#[actix_web::test]
+async fn test_payment_required() {
+    // Arrange
+    let client = start_server().await;
+    let message = json!({
+        "ciphertext": "0x00",
+        "nonce": format!("0x{}", "00".repeat(24)),
+        "receiverKeyUri": "did:kilt:attester#encryption",
+        "senderKeyUri": "did:kilt:light:claimer#encryption",
+    });
+    let message = serde_json::from_str(&message.to_string()).unwrap();
+
+    // Act
+    let result = client
+        .request_attestation(&Uuid::new_v4(), &Uuid::new_v4(), &message)
+        .await;
+
+    // Assert
+    match result {
+        Err(ClientError::PaymentRequired(message)) => {
+            assert_eq!(message.cipher_text, vec![0x12, 0x34]);
+        }
+        _ => panic!("Expected payment required"),
+    }
+}
+
+#[actix_web::test]
+async fn test_get_endpoints() {
+    // Arrange
+    let client = start_server().await;
+
+    // Act
+    let endpoints = client.get_endpoints().await.unwrap();
+
+    // Assert
+    assert_eq!(endpoints, ["auth", "wss"]);
+}
@@ -0,0 +1,293 @@
+use std::{collections::HashMap, fmt};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Claim {
+    #[serde(rename = "cTypeHash")]
+    pub ctype_hash: String,
+    pub contents: serde_json::Value,
+    pub owner: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Credential {
+    pub claim: Claim,
+    pub claim_nonce_map: HashMap<String, String>,
+    pub claim_hashes: Vec<String>,
+    pub delegation_id: Option<String>,
+    #[cfg_attr(feature = "openapi", schema(no_recursion))]
+    pub legitimations: Option<Vec<Credential>>,
+    pub root_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type), sqlx(type_name = "tx_states"))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum TxState {
+    Succeeded,
+    Failed,
+    Pending,
+    InFlight,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "sqlx",
+    derive(sqlx::Type),
+    sqlx(type_name = "review_decisions")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum ReviewDecision {
+    Approved,
+    Rejected,
+    ChangesRequested,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AttestationResponse {
+    pub id: Uuid,
+    pub approved: bool,
+    pub revoked: bool,
+    pub marked_approve: bool,
+    pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub approved_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub revocation_requested_at: Option<NaiveDateTime>,
+    pub rejected: bool,
+    pub rejected_at: Option<NaiveDateTime>,
+    pub rejected_by: Option<String>,
+    pub rejection_reason: Option<String>,
+    pub changes_requested: bool,
+    pub changes_requested_at: Option<NaiveDateTime>,
+    pub delegation_id: Option<String>,
+    pub ctype_hash: String,
+    pub credential: serde_json::Value,
+    pub claimer: String,
+    pub tx_state: Option<TxState>,
+    pub status: AttestationState,
+}
+
+/// The state of an attestation request. It is set by every transition of the request and the
+/// database rejects changes which are not a valid transition.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "sqlx",
+    derive(sqlx::Type),
+    sqlx(type_name = "attestation_states")
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum AttestationState {
+    Pending,
+    ChangesRequested,
+    Rejected,
+    MarkedApproved,
+    InFlight,
+    Approved,
+    Revoked,
+    Deleted,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AttestationTransition {
+    Approve,
+    Reject,
+    RequestChanges,
+    UpdateCredential,
+    Revoke,
+    RequestRevocation,
+}
+
+impl AttestationState {
+    /// Checks whether the transition is allowed in this state. A request with a transaction in
+    /// flight can not be changed until the transaction is finalized.
+    pub fn allows(&self, transition: AttestationTransition) -> bool {
+        matches!(
+            (self, transition),
+            (
+                AttestationState::Pending | AttestationState::MarkedApproved,
+                AttestationTransition::Approve
+            ) | (
+                AttestationState::Pending | AttestationState::ChangesRequested,
+                AttestationTransition::Reject
+            ) | (
+                AttestationState::Pending,
+                AttestationTransition::RequestChanges
+            ) | (
+                AttestationState::ChangesRequested,
+                AttestationTransition::UpdateCredential
+            ) | (
+                AttestationState::Approved,
+                AttestationTransition::Revoke | AttestationTransition::RequestRevocation
+            )
+        )
+    }
+}
+
+impl fmt::Display for AttestationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            AttestationState::Pending => "pending",
+            AttestationState::ChangesRequested => "awaiting changes",
+            AttestationState::Rejected => "rejected",
+            AttestationState::MarkedApproved => "marked approved",
+            AttestationState::InFlight => "in flight",
+            AttestationState::Approved => "approved",
+            AttestationState::Revoked => "revoked",
+            AttestationState::Deleted => "deleted",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl fmt::Display for AttestationTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let transition = match self {
+            AttestationTransition::Approve => "approve",
+            AttestationTransition::Reject => "reject",
+            AttestationTransition::RequestChanges => "request changes for",
+            AttestationTransition::UpdateCredential => "update the credential of",
+            AttestationTransition::Revoke => "revoke",
+            AttestationTransition::RequestRevocation => "request the revocation of",
+        };
+        write!(f, "{}", transition)
+    }
+}
+
+/// A transition which is not allowed in the current state of the attestation request.
+#[derive(Debug, Error)]
+#[error("Can not {transition} an attestation request which is {state}")]
+pub struct InvalidTransition {
+    pub state: AttestationState,
+    pub transition: AttestationTransition,
+}
+
+impl AttestationResponse {
+    /// Returns the current state if the transition is not allowed.
+    pub fn check_transition(
+        &self,
+        transition: AttestationTransition,
+    ) -> Result<(), InvalidTransition> {
+        if self.status.allows(transition) {
+            Ok(())
+        } else {
+            Err(InvalidTransition {
+                state: self.status,
+                transition,
+            })
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Rejection {
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NewComment {
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Comment {
+    pub id: Uuid,
+    pub attestation_request_id: Uuid,
+    pub author: String,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// A previous version of the credential of an attestation request, replaced after a change request.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Revision {
+    pub id: Uuid,
+    pub attestation_request_id: Uuid,
+    pub revision: i32,
+    pub credential: serde_json::Value,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NewAssignment {
+    pub reviewer: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Assignment {
+    pub attestation_request_id: Uuid,
+    pub reviewer: String,
+    pub assigned_by: String,
+    pub assigned_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Review {
+    pub id: Uuid,
+    pub attestation_request_id: Uuid,
+    pub reviewer: String,
+    pub decision: ReviewDecision,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalStatus {
+    pub approvals: i64,
+    pub required_approvals: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationCreatedOverTime {
+    pub date: Option<NaiveDateTime>,
+    pub total_attestations_created: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationKPIs {
+    pub attestations_created_over_time: Vec<AttestationCreatedOverTime>,
+    pub attestations_not_approved: i64,
+    pub attestations_approved: i64,
+    pub attestations_revoked: i64,
+    pub attestations_rejected: i64,
+    pub attestations_failed: i64,
+    pub total_claimers: i64,
+    /// Seconds between the creation and the approval of attested requests.
+    pub time_to_approval: TimeToApproval,
+    /// Share of failed transactions among all finished transactions, including failed attempts
+    /// of requests which were retried.
+    pub tx_failure_rate: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TimeToApproval {
+    pub median: Option<f64>,
+    pub p95: Option<f64>,
+}
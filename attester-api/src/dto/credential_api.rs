@@ -0,0 +1,353 @@
+use chrono::NaiveDateTime;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sodiumoxide::crypto::{box_, box_::Nonce};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::{
+    utils::{hex_nonce, prefixed_hex},
+    Claim, Credential,
+};
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ChallengeData {
+    #[serde(rename = "dAppName")]
+    pub app_name: String,
+    #[serde(rename = "dAppEncryptionKeyUri")]
+    pub encryption_key_uri: String,
+    pub challenge: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeResponse {
+    pub encryption_key_uri: String,
+    #[serde(with = "prefixed_hex")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub encrypted_challenge: Vec<u8>,
+    #[serde(with = "hex_nonce")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub nonce: box_::Nonce,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageBody<T> {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub content: T,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Message<T> {
+    pub body: MessageBody<T>,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64,
+    pub sender: String,
+    pub receiver: String,
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    #[serde(rename = "inReplyTo")]
+    pub in_reply_to: Option<String>,
+    pub references: Option<Vec<String>>,
+}
+
+/// Error of parsing the content of a received message.
+#[derive(Debug, Error)]
+pub enum MessageError {
+    #[error("Expected message of type {expected} but received {received}")]
+    UnexpectedType {
+        expected: &'static str,
+        received: String,
+    },
+    #[error("Invalid message content: {0}")]
+    Content(#[from] serde_json::Error),
+}
+
+/// Content of a Credential API message. `TYPE` is the message type used in the message body.
+pub trait MessageContent {
+    const TYPE: &'static str;
+}
+
+impl<T: MessageContent> Message<T> {
+    pub fn new(content: T, sender: &str, receiver: &str, in_reply_to: Option<String>) -> Self {
+        Message {
+            body: MessageBody {
+                type_: T::TYPE.to_string(),
+                content,
+            },
+            created_at: chrono::Utc::now().timestamp_millis() as u64,
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            message_id: Uuid::new_v4().to_string(),
+            in_reply_to,
+            references: None,
+        }
+    }
+}
+
+impl Message<serde_json::Value> {
+    /// Parses the content of a received message after checking its type.
+    pub fn into_content<T: MessageContent + DeserializeOwned>(
+        self,
+    ) -> Result<Message<T>, MessageError> {
+        if self.body.type_ != T::TYPE {
+            return Err(MessageError::UnexpectedType {
+                expected: T::TYPE,
+                received: self.body.type_,
+            });
+        }
+
+        Ok(Message {
+            body: MessageBody {
+                type_: self.body.type_,
+                content: serde_json::from_value(self.body.content)?,
+            },
+            created_at: self.created_at,
+            sender: self.sender,
+            receiver: self.receiver,
+            message_id: self.message_id,
+            in_reply_to: self.in_reply_to,
+            references: self.references,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EncryptedMessage {
+    #[serde(rename = "ciphertext")]
+    #[serde(with = "prefixed_hex")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub cipher_text: Vec<u8>,
+    #[serde(with = "hex_nonce")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub nonce: Nonce,
+    #[serde(rename = "receiverKeyUri")]
+    pub receiver_key_uri: String,
+    #[serde(rename = "senderKeyUri")]
+    pub sender_key_uri: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubmitTermsMessageContent {
+    pub claim: Claim,
+    pub legitimations: Option<Vec<Credential>>,
+    /// Legitimations which the claimer has to embed into the requested credential.
+    #[serde(
+        rename = "requiredLegitimations",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub required_legitimations: Vec<CTypeRequest>,
+    #[serde(rename = "delegationId", skip_serializing_if = "Option::is_none")]
+    pub delegation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<AttesterSignedQuote>,
+}
+
+impl MessageContent for SubmitTermsMessageContent {
+    const TYPE: &'static str = "submit-terms";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestAttestationMessageContent {
+    pub credential: Credential,
+    pub quote: Option<QuoteAgreement>,
+}
+
+impl MessageContent for RequestAttestationMessageContent {
+    const TYPE: &'static str = "request-attestation";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attestation {
+    pub claim_hash: String,
+    #[serde(rename = "cTypeHash")]
+    pub ctype_hash: String,
+    pub owner: String,
+    pub delegation_id: Option<String>,
+    pub revoked: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubmitAttestationMessageContent {
+    pub attestation: Attestation,
+}
+
+impl MessageContent for SubmitAttestationMessageContent {
+    const TYPE: &'static str = "submit-attestation";
+}
+
+/// Root hash of the credential which is not going to be attested.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RejectAttestationMessageContent(pub String);
+
+impl MessageContent for RejectAttestationMessageContent {
+    const TYPE: &'static str = "reject-attestation";
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RejectMessageContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl MessageContent for RejectMessageContent {
+    const TYPE: &'static str = "reject";
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ErrorMessageContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl MessageContent for ErrorMessageContent {
+    const TYPE: &'static str = "error";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CTypeRequest {
+    #[serde(rename = "cTypeHash")]
+    pub ctype_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_attesters: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_properties: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestCredentialMessageContent {
+    #[serde(rename = "cTypes")]
+    pub ctypes: Vec<CTypeRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+}
+
+impl MessageContent for RequestCredentialMessageContent {
+    const TYPE: &'static str = "request-credential";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimerSignature {
+    pub key_uri: String,
+    pub signature: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialPresentation {
+    #[serde(flatten)]
+    pub credential: Credential,
+    pub claimer_signature: ClaimerSignature,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SubmitCredentialMessageContent(pub Vec<CredentialPresentation>);
+
+impl MessageContent for SubmitCredentialMessageContent {
+    const TYPE: &'static str = "submit-credential";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedCredential {
+    pub root_hash: String,
+    pub claim: Claim,
+    pub attester: String,
+}
+
+/// Request for credentials which was sent to a wallet session and waits for the presentations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VerificationRequest {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub requested_by: String,
+    pub ctypes: serde_json::Value,
+    pub challenge: String,
+    pub created_at: NaiveDateTime,
+    pub verified_at: Option<NaiveDateTime>,
+    pub verified_credentials: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CostBreakdown {
+    pub tax: serde_json::Map<String, serde_json::Value>,
+    pub net: serde_json::Number,
+    pub gross: serde_json::Number,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    pub attester_did: String,
+    #[serde(rename = "cTypeHash")]
+    pub ctype_hash: String,
+    pub cost: CostBreakdown,
+    pub currency: String,
+    pub timeframe: String,
+    pub terms_and_conditions: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DidSignature {
+    pub key_uri: String,
+    pub signature: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttesterSignedQuote {
+    #[serde(flatten)]
+    pub quote: Quote,
+    pub attester_signature: DidSignature,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteAgreement {
+    #[serde(flatten)]
+    pub attester_signed_quote: AttesterSignedQuote,
+    pub root_hash: String,
+    pub claimer_did: String,
+    pub claimer_signature: DidSignature,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestPaymentMessageContent {
+    pub claim_hash: String,
+}
+
+impl MessageContent for RequestPaymentMessageContent {
+    const TYPE: &'static str = "request-payment";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmPaymentMessageContent {
+    pub claim_hash: String,
+    pub tx_hash: String,
+    pub block_hash: String,
+}
+
+impl MessageContent for ConfirmPaymentMessageContent {
+    const TYPE: &'static str = "confirm-payment";
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DelegationPermissions {
+    #[serde(default)]
+    pub attest: bool,
+    #[serde(default)]
+    pub delegate: bool,
+}
+
+/// Permission of a delegation node to attest claims.
+const ATTEST_PERMISSION: u32 = 0b0001;
+/// Permission of a delegation node to add child nodes.
+const DELEGATE_PERMISSION: u32 = 0b0010;
+
+impl From<DelegationPermissions> for u32 {
+    fn from(permissions: DelegationPermissions) -> Self {
+        let mut bits = 0;
+        if permissions.attest {
+            bits |= ATTEST_PERMISSION;
+        }
+        if permissions.delegate {
+            bits |= DELEGATE_PERMISSION;
+        }
+        bits
+    }
+}
+
+impl From<u32> for DelegationPermissions {
+    fn from(bits: u32) -> Self {
+        DelegationPermissions {
+            attest: bits & ATTEST_PERMISSION != 0,
+            delegate: bits & DELEGATE_PERMISSION != 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NewDelegationHierarchy {
+    pub ctype_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationRequest {
+    pub delegate: String,
+    pub permissions: DelegationPermissions,
+}
+
+/// Data which the delegate has to sign with its authentication key to accept a delegation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationSigningPayload {
+    pub delegation_id: String,
+    pub hierarchy_root_id: String,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NewDelegation {
+    pub delegation_id: String,
+    pub delegate: String,
+    pub permissions: DelegationPermissions,
+    pub delegate_signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationNode {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub children: Vec<String>,
+    pub owner: String,
+    pub permissions: DelegationPermissions,
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationHierarchy {
+    pub root_id: String,
+    pub ctype_hash: String,
+    pub nodes: Vec<DelegationNode>,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Key URIs of the attester DID as stored on chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    pub uri: String,
+    pub authentication_key: String,
+    pub attestation_key: Option<String>,
+    pub delegation_key: Option<String>,
+    pub key_agreement_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NewServiceEndpoint {
+    pub id: String,
+    pub service_types: Vec<String>,
+    pub urls: Vec<String>,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of every error response. The code is stable and meant for clients, the message is meant
+/// for humans and may change.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    pub code: String,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+}
@@ -0,0 +1,16 @@
+mod attestation_requests;
+mod credential_api;
+mod delegation;
+mod did;
+mod error;
+mod evidence;
+mod query;
+mod utils;
+
+pub use attestation_requests::*;
+pub use credential_api::*;
+pub use delegation::*;
+pub use did::*;
+pub use error::*;
+pub use evidence::*;
+pub use query::*;
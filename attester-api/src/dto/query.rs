@@ -0,0 +1,40 @@
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct Query {
+    pub range: Option<String>,
+    pub sort: Option<String>,
+    pub filter: Option<String>,
+}
+
+/// Size of the time buckets of the KPIs.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum KpiBucket {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl KpiBucket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KpiBucket::Day => "day",
+            KpiBucket::Week => "week",
+            KpiBucket::Month => "month",
+        }
+    }
+}
+
+/// Restricts the KPIs to requests created in the date range (both inclusive) and of a CType.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+#[serde(rename_all = "camelCase")]
+pub struct KpiFilter {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+    pub ctype_hash: Option<String>,
+    #[serde(default)]
+    pub bucket: KpiBucket,
+}
@@ -0,0 +1,12 @@
+//! DTOs and routes of the REST API of the attester service, and a client for it.
+//!
+//! The `sqlx` and `openapi` features derive the database and OpenAPI traits which the server
+//! needs. The `client` feature (enabled by default) provides [`AttesterClient`].
+
+#[cfg(feature = "client")]
+mod client;
+pub mod dto;
+pub mod routes;
+
+#[cfg(feature = "client")]
+pub use client::{AttesterClient, ClientError, Page};
@@ -0,0 +1,10 @@
+//! Scopes of the REST API, shared by the server and the client.
+
+pub const ATTESTATION_REQUEST: &str = "/api/v1/attestation_request";
+pub const CHALLENGE: &str = "/api/v1/challenge";
+pub const CREDENTIAL: &str = "/api/v1/credential";
+pub const DELEGATION: &str = "/api/v1/delegation";
+pub const DID: &str = "/api/v1/did";
+pub const ENDPOINTS: &str = "/api/v1/endpoints";
+pub const EVIDENCE: &str = "/api/v1/evidence";
+pub const OPENAPI: &str = "/api/v1/openapi.json";
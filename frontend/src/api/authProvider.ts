@@ -40,7 +40,7 @@ export const authProvider: AuthProvider = {
       return Promise.resolve()
     }
     const status = error.status
-    if (status === 401 || status === 403) {
+    if (status === 401) {
       localStorage.removeItem('token')
       localStorage.removeItem('role')
       return Promise.reject()
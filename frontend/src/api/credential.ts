@@ -11,16 +11,51 @@ export async function fetchCredential(extension: InjectedWindowProvider, session
 
   const getTermsResponse = await client.post(`${credentialUrl}/terms/${sessionId}/${attestationId}`, sessionId)
 
-  const getCredentialRequestFromExtension = await new Promise((resolve, reject) => {
+  const getCredentialRequestFromExtension = await exchangeMessage(extension, getTermsResponse.data)
+
+  // The backend answers with 402 and a request-payment message if the CType requires a payment.
+  const requestAttestationResponse = await client.post(
+    `${credentialUrl}/${sessionId}/${attestationId}`,
+    getCredentialRequestFromExtension,
+    { validateStatus: (status) => status === 200 || status === 402 }
+  )
+
+  if (requestAttestationResponse.status === 402) {
+    const paymentConfirmation = await exchangeMessage(extension, requestAttestationResponse.data)
+    const confirmPaymentResponse = await client.post(
+      `${credentialUrl}/payment/${sessionId}/${attestationId}`,
+      paymentConfirmation
+    )
+    await extension.send(confirmPaymentResponse.data)
+    return
+  }
+
+  // The submit-attestation or reject-attestation message is forwarded without expecting a reply.
+  await extension.send(requestAttestationResponse.data)
+}
+
+export async function deliverAttestation(extension: InjectedWindowProvider, sessionId: string, attestationId: string) {
+  const apiUrl = getBackendUrl()
+
+  const client = await getAxiosClient()
+
+  const submitAttestationResponse = await client.post(`${apiUrl}/credential/attestation/${sessionId}/${attestationId}`)
+
+  await extension.send(submitAttestationResponse.data)
+}
+
+function exchangeMessage(
+  extension: InjectedWindowProvider,
+  message: Parameters<InjectedWindowProvider['send']>[0]
+): Promise<unknown> {
+  return new Promise((resolve, reject) => {
     try {
-      extension.listen(async (credentialRequest: unknown) => {
-        resolve(credentialRequest)
+      extension.listen(async (response: unknown) => {
+        resolve(response)
       })
-      extension.send(getTermsResponse.data)
+      extension.send(message)
     } catch (e) {
       reject(e)
     }
   })
-
-  client.post(`${credentialUrl}/${sessionId}/${attestationId}`, getCredentialRequestFromExtension)
 }
@@ -16,7 +16,7 @@ import { getAxiosClient } from '../api/dataProvider'
 import { getSession } from '../api/session'
 import { getBackendUrl, isUserAdmin } from '../utils/utils'
 import { InjectedWindowProvider } from '@kiltprotocol/kilt-extension-api'
-import { fetchCredential } from '../api/credential'
+import { deliverAttestation, fetchCredential } from '../api/credential'
 
 export default function AttestationList() {
   const apiUrl = getBackendUrl()
@@ -174,7 +174,12 @@ export default function AttestationList() {
       try {
         const { session, sessionId } = await getSession(extension)
 
-        await fetchCredential(session, sessionId, record.id)
+        // Attestations approved by an admin are already on chain and only have to be delivered.
+        if (record.approved) {
+          await deliverAttestation(session, sessionId, record.id)
+        } else {
+          await fetchCredential(session, sessionId, record.id)
+        }
         refresh()
         notify('Claim created')
         setIsLoading(false)
@@ -192,7 +197,7 @@ export default function AttestationList() {
               color="success"
               aria-label="claim"
               size="small"
-              disabled={!record.marked_approve || record.approved || record.txState === 'InFlight'}
+              disabled={(!record.marked_approve && !record.approved) || record.revoked || record.txState === 'InFlight'}
               onClick={handleClick}
               sx={{ marginLeft: '1em', marginRight: '1em' }}
             >
@@ -223,6 +228,7 @@ export default function AttestationList() {
         <DateField source="created_at" />
         <DateField source="approved_at" />
         <DateField source="revoked_at" />
+        <TextField source="status" />
         <TextField source="tx_state" />
         <URLField source="ctype_hash" baseURL="https://ctypehub.galaniprojects.de/ctype/" />
         {isUserAdmin() && <ApproveButton />}
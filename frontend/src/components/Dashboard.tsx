@@ -1,4 +1,5 @@
 import { useEffect, useState } from 'react'
+import { isAxiosError } from 'axios'
 import { LineChart, Line, XAxis, YAxis, CartesianGrid, Tooltip, ResponsiveContainer } from 'recharts'
 import Card from '@mui/material/Card'
 import Typography from '@mui/material/Typography'
@@ -31,6 +32,10 @@ const Dashboard = () => {
 
         setKpi(res.data)
       } catch (error) {
+        if (isAxiosError(error) && error.response?.status === 403) {
+          setErrorMessage('Only admins can see the statistics.')
+          return
+        }
         console.error('Error fetching data:', error)
         setErrorMessage(`Error fetching data: ${error}`)
       }
@@ -14,4 +14,5 @@ export interface AttestationRequest {
   approved_at?: string
   revoked_at?: string
   txState: string
+  status: string
 }
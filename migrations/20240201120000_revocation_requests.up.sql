@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE attestation_requests ADD COLUMN revocation_requested_at TIMESTAMP;
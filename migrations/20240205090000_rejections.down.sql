@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE attestation_requests DROP COLUMN rejection_reason;
+ALTER TABLE attestation_requests DROP COLUMN rejected_by;
+ALTER TABLE attestation_requests DROP COLUMN rejected_at;
+ALTER TABLE attestation_requests DROP COLUMN rejected;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE attestation_requests ADD COLUMN rejected BOOLEAN DEFAULT false NOT NULL;
+ALTER TABLE attestation_requests ADD COLUMN rejected_at TIMESTAMP;
+ALTER TABLE attestation_requests ADD COLUMN rejected_by VARCHAR(255);
+ALTER TABLE attestation_requests ADD COLUMN rejection_reason TEXT;
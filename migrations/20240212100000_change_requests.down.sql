@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE attestation_request_revisions;
+DROP TABLE attestation_request_comments;
+ALTER TABLE attestation_requests DROP COLUMN changes_requested_at;
+ALTER TABLE attestation_requests DROP COLUMN changes_requested;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+ALTER TABLE attestation_requests ADD COLUMN changes_requested BOOLEAN DEFAULT false NOT NULL;
+ALTER TABLE attestation_requests ADD COLUMN changes_requested_at TIMESTAMP;
+
+CREATE TABLE IF NOT EXISTS attestation_request_comments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4() NOT NULL,
+    attestation_request_id UUID NOT NULL REFERENCES attestation_requests(id),
+    author VARCHAR(255) NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS attestation_request_revisions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4() NOT NULL,
+    attestation_request_id UUID NOT NULL REFERENCES attestation_requests(id),
+    revision INTEGER NOT NULL,
+    credential jsonb NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL,
+    UNIQUE (attestation_request_id, revision)
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE attestation_request_reviews;
+DROP TABLE attestation_request_assignments;
+DROP TYPE review_decisions;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+CREATE TYPE review_decisions AS ENUM ('Approved', 'Rejected', 'ChangesRequested');
+
+CREATE TABLE IF NOT EXISTS attestation_request_assignments (
+    attestation_request_id UUID NOT NULL REFERENCES attestation_requests(id),
+    reviewer VARCHAR(255) NOT NULL,
+    assigned_by VARCHAR(255) NOT NULL,
+    assigned_at TIMESTAMP DEFAULT now() NOT NULL,
+    PRIMARY KEY (attestation_request_id, reviewer)
+);
+
+CREATE TABLE IF NOT EXISTS attestation_request_reviews (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4() NOT NULL,
+    attestation_request_id UUID NOT NULL REFERENCES attestation_requests(id),
+    reviewer VARCHAR(255) NOT NULL,
+    decision review_decisions NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL
+);
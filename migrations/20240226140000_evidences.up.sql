@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS evidences (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4() NOT NULL,
+    attestation_request_id UUID NOT NULL REFERENCES attestation_requests(id),
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size BIGINT NOT NULL,
+    uploaded_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL,
+    purged_at TIMESTAMP
+);
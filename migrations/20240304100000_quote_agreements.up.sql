@@ -0,0 +1,9 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS quote_agreements (
+    attestation_request_id UUID PRIMARY KEY REFERENCES attestation_requests(id),
+    agreement JSONB NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL,
+    payment_block_hash VARCHAR(66),
+    payment_tx_hash VARCHAR(66) UNIQUE,
+    paid_at TIMESTAMP
+);
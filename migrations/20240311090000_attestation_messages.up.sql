@@ -0,0 +1,7 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS attestation_messages (
+    attestation_request_id UUID PRIMARY KEY REFERENCES attestation_requests(id),
+    content JSONB NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL,
+    delivered_at TIMESTAMP
+);
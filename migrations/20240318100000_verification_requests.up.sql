@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS verification_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4() NOT NULL,
+    session_id UUID NOT NULL,
+    requested_by VARCHAR(255) NOT NULL,
+    ctypes JSONB NOT NULL,
+    challenge VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL,
+    verified_at TIMESTAMP,
+    verified_credentials JSONB
+);
+
+CREATE INDEX IF NOT EXISTS verification_requests_session_id ON verification_requests(session_id);
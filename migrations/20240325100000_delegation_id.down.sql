@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE attestation_requests DROP COLUMN delegation_id;
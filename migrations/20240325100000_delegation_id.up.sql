@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE attestation_requests ADD COLUMN delegation_id VARCHAR(66);
+
+UPDATE attestation_requests SET delegation_id = credential->>'delegationId';
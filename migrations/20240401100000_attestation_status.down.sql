@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS attestation_state ON attestation_requests;
+DROP FUNCTION IF EXISTS update_attestation_state;
+
+ALTER TABLE attestation_requests
+    DROP COLUMN status,
+    DROP CONSTRAINT revoked_requires_approved,
+    DROP CONSTRAINT approved_or_rejected,
+    DROP CONSTRAINT marked_approve_or_rejected;
+
+DROP TABLE attestation_state_transitions;
+DROP TYPE attestation_states;
@@ -0,0 +1,73 @@
+-- Add up migration script here
+CREATE TYPE attestation_states AS ENUM ('Pending', 'ChangesRequested', 'Rejected', 'MarkedApproved', 'InFlight', 'Approved', 'Revoked', 'Deleted');
+
+CREATE TABLE attestation_state_transitions (
+    from_state attestation_states NOT NULL,
+    to_state attestation_states NOT NULL,
+    PRIMARY KEY (from_state, to_state)
+);
+
+INSERT INTO attestation_state_transitions (from_state, to_state) VALUES
+    ('Pending', 'ChangesRequested'),
+    ('Pending', 'Rejected'),
+    ('Pending', 'MarkedApproved'),
+    ('Pending', 'InFlight'),
+    ('Pending', 'Deleted'),
+    ('ChangesRequested', 'Pending'),
+    ('ChangesRequested', 'Rejected'),
+    ('ChangesRequested', 'Deleted'),
+    ('Rejected', 'Deleted'),
+    ('MarkedApproved', 'InFlight'),
+    ('MarkedApproved', 'Approved'),
+    ('MarkedApproved', 'Deleted'),
+    ('InFlight', 'Pending'),
+    ('InFlight', 'MarkedApproved'),
+    ('InFlight', 'Approved'),
+    ('InFlight', 'Revoked'),
+    ('Approved', 'InFlight'),
+    ('Revoked', 'Deleted');
+
+-- Requests which were revoked without being approved or rejected after the approval are
+-- normalized, so that the constraints below hold.
+UPDATE attestation_requests SET approved = true WHERE revoked AND NOT approved;
+UPDATE attestation_requests SET rejected = false WHERE approved AND rejected;
+UPDATE attestation_requests SET marked_approve = false WHERE rejected AND marked_approve;
+
+ALTER TABLE attestation_requests
+    ADD CONSTRAINT revoked_requires_approved CHECK (NOT revoked OR approved),
+    ADD CONSTRAINT approved_or_rejected CHECK (NOT (approved AND rejected)),
+    ADD CONSTRAINT marked_approve_or_rejected CHECK (NOT (marked_approve AND rejected));
+
+ALTER TABLE attestation_requests ADD COLUMN status attestation_states NOT NULL DEFAULT 'Pending';
+
+UPDATE attestation_requests SET status = CASE
+    WHEN deleted_at IS NOT NULL THEN 'Deleted'
+    WHEN revoked THEN 'Revoked'
+    WHEN tx_state = 'InFlight' THEN 'InFlight'
+    WHEN approved THEN 'Approved'
+    WHEN rejected THEN 'Rejected'
+    WHEN changes_requested THEN 'ChangesRequested'
+    WHEN marked_approve THEN 'MarkedApproved'
+    ELSE 'Pending'
+END::attestation_states;
+
+-- The status is set by the transitions themselves. Changes of the status are only allowed along
+-- the transitions of attestation_state_transitions.
+CREATE OR REPLACE FUNCTION update_attestation_state()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status IS DISTINCT FROM OLD.status AND NOT EXISTS (
+        SELECT 1 FROM attestation_state_transitions
+        WHERE from_state = OLD.status AND to_state = NEW.status
+    ) THEN
+        RAISE EXCEPTION 'Invalid attestation state transition from % to %', OLD.status, NEW.status
+            USING ERRCODE = 'P0409';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER attestation_state
+BEFORE UPDATE OF status ON attestation_requests
+FOR EACH ROW
+EXECUTE FUNCTION update_attestation_state();
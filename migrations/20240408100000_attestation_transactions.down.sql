@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS attestation_transaction ON attestation_requests;
+DROP FUNCTION IF EXISTS record_attestation_transaction;
+DROP TABLE attestation_transactions;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS attestation_transactions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4() NOT NULL,
+    attestation_request_id UUID NOT NULL REFERENCES attestation_requests(id),
+    tx_state tx_states NOT NULL,
+    created_at TIMESTAMP DEFAULT now() NOT NULL
+);
+
+-- Every finished transaction is recorded, so that failures stay visible after a successful retry.
+CREATE OR REPLACE FUNCTION record_attestation_transaction()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO attestation_transactions (attestation_request_id, tx_state)
+    VALUES (NEW.id, NEW.tx_state);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER attestation_transaction
+AFTER UPDATE OF tx_state ON attestation_requests
+FOR EACH ROW
+WHEN (NEW.tx_state IN ('Succeeded', 'Failed') AND NEW.tx_state IS DISTINCT FROM OLD.tx_state)
+EXECUTE FUNCTION record_attestation_transaction();
+
+INSERT INTO attestation_transactions (attestation_request_id, tx_state)
+SELECT id, tx_state FROM attestation_requests WHERE tx_state IN ('Succeeded', 'Failed');
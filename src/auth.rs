@@ -4,7 +4,7 @@ use hmac::{Hmac, Mac};
 use jwt::VerifyWithKey;
 use sha2::Sha256;
 
-use crate::AppState;
+use crate::{error::AppError, AppState};
 
 #[allow(dead_code)]
 #[derive(serde::Deserialize)]
@@ -32,7 +32,10 @@ pub async fn jwt_validator(
     let http_req = req.request();
 
     let app_data = http_req.app_data::<web::Data<AppState>>().ok_or((
-        actix_web::error::ErrorInternalServerError("App data are not set"),
+        AppError::ActixWeb(actix_web::error::ErrorInternalServerError(
+            "App data are not set",
+        ))
+        .into(),
         ServiceRequest::from_request(http_req.to_owned()),
     ))?;
 
@@ -42,14 +45,17 @@ pub async fn jwt_validator(
 
     let secret: Hmac<Sha256> = Hmac::new_from_slice(jwt_secret.as_bytes()).map_err(|_| {
         (
-            actix_web::error::ErrorInternalServerError("Secret is in wrong format"),
+            AppError::ActixWeb(actix_web::error::ErrorInternalServerError(
+                "Secret is in wrong format",
+            ))
+            .into(),
             ServiceRequest::from_request(http_req.to_owned()),
         )
     })?;
 
     let jwt_payload: JWTPayload = token.verify_with_key(&secret).map_err(|_| {
         (
-            actix_web::error::ErrorUnauthorized("JWT Verification did not succeed"),
+            AppError::Unauthorized("JWT Verification did not succeed").into(),
             ServiceRequest::from_request(http_req.to_owned()),
         )
     })?;
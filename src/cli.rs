@@ -1,14 +1,62 @@
 use anyhow::Context;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use subxt::{ext::sp_core::Pair, OnlineClient};
 
-use crate::Configuration;
+use crate::{
+    kilt::{
+        create_did, did_exists, get_did_document, get_did_uri, parse_key_id, update_did,
+        verify_did_keys, DidUpdate, KiltConfig,
+    },
+    Configuration,
+};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
-    #[arg(env)]
+    /// Path to the configuration file.
+    #[arg(short, long, env)]
     config: PathBuf,
+    /// Runs an administrative command instead of starting the server.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manages the keys and service endpoints of the attester DID.
+    #[command(subcommand)]
+    Did(DidCommand),
+}
+
+#[derive(Subcommand)]
+pub enum DidCommand {
+    /// Prints the key URIs of the attester DID.
+    Show,
+    /// Creates the attester DID with the keys of the configuration if it doesn't exist yet and
+    /// checks that the keys on chain match the configuration.
+    Create,
+    /// Sets the attestation key to the key of `attesterAttestationSeed`.
+    SetAttestationKey,
+    /// Sets the delegation key to the key of `attesterDelegationSeed`.
+    SetDelegationKey,
+    /// Adds the public key of `session.naclSecretKey` as key agreement key.
+    AddKeyAgreementKey,
+    /// Removes a key agreement key, e.g. after it was rotated.
+    RemoveKeyAgreementKey {
+        /// The key URI or its fragment.
+        key_id: String,
+    },
+    /// Adds a service endpoint.
+    AddService {
+        id: String,
+        #[arg(long = "type", required = true)]
+        service_types: Vec<String>,
+        #[arg(long = "url", required = true)]
+        urls: Vec<String>,
+    },
+    /// Removes a service endpoint.
+    RemoveService { id: String },
 }
 
 impl Cli {
@@ -20,3 +68,99 @@ impl Cli {
         Ok(config)
     }
 }
+
+impl Command {
+    pub async fn run(&self, config: &Configuration) -> anyhow::Result<()> {
+        match self {
+            Command::Did(command) => command.run(config).await,
+        }
+    }
+}
+
+impl DidCommand {
+    fn get_update(&self, config: &Configuration) -> anyhow::Result<Option<DidUpdate>> {
+        let update = match self {
+            DidCommand::Show | DidCommand::Create => return Ok(None),
+            DidCommand::SetAttestationKey => {
+                DidUpdate::SetAttestationKey(config.get_credential_signer()?.signer().public())
+            }
+            DidCommand::SetDelegationKey => {
+                DidUpdate::SetDelegationKey(config.get_delegation_signer()?.signer().public())
+            }
+            DidCommand::AddKeyAgreementKey => {
+                DidUpdate::AddKeyAgreementKey(config.get_nacl_secret_key()?.public_key())
+            }
+            DidCommand::RemoveKeyAgreementKey { key_id } => DidUpdate::RemoveKeyAgreementKey(
+                parse_key_id(key_id).map_err(|e| anyhow::anyhow!(e.to_string()))?,
+            ),
+            DidCommand::AddService {
+                id,
+                service_types,
+                urls,
+            } => DidUpdate::AddServiceEndpoint {
+                id: id.clone(),
+                service_types: service_types.clone(),
+                urls: urls.clone(),
+            },
+            DidCommand::RemoveService { id } => DidUpdate::RemoveServiceEndpoint(id.clone()),
+        };
+        Ok(Some(update))
+    }
+
+    pub async fn run(&self, config: &Configuration) -> anyhow::Result<()> {
+        let did_address = config.get_did()?;
+        let chain_client = OnlineClient::<KiltConfig>::from_url(&config.endpoint).await?;
+
+        if let DidCommand::Create = self {
+            create_did_if_missing(config, &chain_client).await?;
+        }
+
+        if let Some(update) = self.get_update(config)? {
+            update_did(
+                update,
+                &did_address,
+                &chain_client,
+                &config.get_payer_signer()?,
+                &config.get_did_authentication_signer()?,
+            )
+            .await
+            .context("Updating the DID failed")?;
+        }
+
+        let document = get_did_document(&did_address, &chain_client)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        Ok(())
+    }
+}
+
+async fn create_did_if_missing(
+    config: &Configuration,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> anyhow::Result<()> {
+    let keys = config.get_did_keys()?;
+    let did_address = config.get_did()?;
+
+    if did_exists(&did_address, chain_client)
+        .await
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?
+    {
+        log::info!("DID {} already exists", get_did_uri(&did_address));
+    } else {
+        create_did(
+            &keys,
+            chain_client,
+            &config.get_payer_signer()?,
+            &config.get_did_authentication_signer()?,
+        )
+        .await
+        .context("Creating the DID failed")?;
+        log::info!("DID {} created", get_did_uri(&did_address));
+    }
+
+    verify_did_keys(&keys, chain_client)
+        .await
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .context("DID on chain does not match the configuration")
+}
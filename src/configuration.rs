@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
-use sodiumoxide::crypto::box_::SecretKey;
+use sodiumoxide::crypto::{box_::SecretKey, secretbox};
+use std::{collections::HashMap, path::PathBuf};
 use subxt::{
     ext::sp_core::{sr25519::Pair, Pair as PairTrait},
     tx::PairSigner,
     utils::AccountId32,
 };
 
-use crate::kilt::KiltConfig;
+use crate::{
+    database::dto::{CTypeRequest, CostBreakdown},
+    kilt::{DidKeys, KiltConfig},
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,12 +22,21 @@ pub struct Configuration {
     pub well_known_did_config: WellKnownDidConfig,
     attester_did_seed: String,
     attester_attestation_seed: String,
+    attester_delegation_seed: Option<String>,
     pub database_url: String,
     pub front_end_path: String,
     pub jwt_secret: String,
     pub payer_seed: String,
     pub app_name: String,
     pub auth_url: String,
+    #[serde(default)]
+    pub ctypes: HashMap<String, CTypeConfig>,
+    pub evidence: Option<EvidenceConfig>,
+    /// A warning is logged if the payer holds less KILT.
+    pub min_payer_balance: Option<serde_json::Number>,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    pub metrics: Option<MetricsConfig>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -44,12 +57,124 @@ pub struct WellKnownDidConfig {
     pub seed: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvidenceConfig {
+    pub path: PathBuf,
+    pub encryption_key: String,
+    #[serde(default = "default_max_file_size")]
+    pub max_file_size: usize,
+}
+
+fn default_max_file_size() -> usize {
+    10 * 1024 * 1024
+}
+
+impl EvidenceConfig {
+    pub fn get_encryption_key(&self) -> anyhow::Result<secretbox::Key> {
+        let raw_key = hex::decode(self.encryption_key.trim_start_matches("0x"))?;
+        secretbox::Key::from_slice(&raw_key).ok_or(anyhow::anyhow!(
+            "Evidence encryption key must have 32 bytes"
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// OTLP/HTTP endpoint of a collector to which the spans are exported,
+    /// e.g. `http://localhost:4318/v1/traces`.
+    pub otlp_endpoint: Option<String>,
+}
+
+/// The Prometheus exporter is served on its own port, which should not be exposed publicly.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsConfig {
+    pub port: u16,
+    /// Seconds between two reads of the payer balance from the chain.
+    #[serde(default = "default_balance_refresh_interval")]
+    pub balance_refresh_interval: u64,
+}
+
+fn default_balance_refresh_interval() -> u64 {
+    60
+}
+
+/// Settings which apply to the attestation requests of a single CType, keyed by the CType hash.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CTypeConfig {
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: u32,
+    pub quote: Option<QuoteConfig>,
+    /// Credentials which the claimer has to embed as legitimations before being attested.
+    #[serde(default)]
+    pub legitimations: Vec<CTypeRequest>,
+    /// Delegation node of the attester under which the credentials are attested.
+    pub delegation_id: Option<String>,
+}
+
+/// Terms which are offered to the claimer together with the claim in the `submit-terms` message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteConfig {
+    pub cost: CostBreakdown,
+    pub currency: String,
+    pub timeframe: String,
+    pub terms_and_conditions: String,
+    /// If set, the gross cost has to be transferred in KILT to the payer account before attesting.
+    #[serde(default)]
+    pub require_payment: bool,
+}
+
+impl Default for CTypeConfig {
+    fn default() -> Self {
+        CTypeConfig {
+            required_approvals: default_required_approvals(),
+            quote: None,
+            legitimations: Vec::new(),
+            delegation_id: None,
+        }
+    }
+}
+
+fn default_required_approvals() -> u32 {
+    1
+}
+
 impl Configuration {
     pub fn get_credential_signer(&self) -> anyhow::Result<PairSigner<KiltConfig, Pair>> {
         let pair = Pair::from_string_with_seed(&self.attester_attestation_seed, None)?.0;
         Ok(PairSigner::new(pair))
     }
 
+    /// Returns the signer of the delegation key. Falls back to the attestation key if no separate
+    /// delegation seed is configured.
+    pub fn get_delegation_signer(&self) -> anyhow::Result<PairSigner<KiltConfig, Pair>> {
+        let seed = self
+            .attester_delegation_seed
+            .as_ref()
+            .unwrap_or(&self.attester_attestation_seed);
+        let pair = Pair::from_string_with_seed(seed, None)?.0;
+        Ok(PairSigner::new(pair))
+    }
+
+    pub fn get_did_authentication_signer(&self) -> anyhow::Result<PairSigner<KiltConfig, Pair>> {
+        let pair = Pair::from_string_with_seed(&self.attester_did_seed, None)?.0;
+        Ok(PairSigner::new(pair))
+    }
+
     pub fn get_payer_signer(&self) -> anyhow::Result<PairSigner<KiltConfig, Pair>> {
         let pair = Pair::from_string_with_seed(&self.payer_seed, None)?.0;
         Ok(PairSigner::new(pair))
@@ -60,6 +185,16 @@ impl Configuration {
         Ok(pair.public().into())
     }
 
+    /// Returns the keys which the attester DID should have according to the configured seeds.
+    pub fn get_did_keys(&self) -> anyhow::Result<DidKeys> {
+        Ok(DidKeys {
+            authentication_key: self.get_did_authentication_signer()?.signer().public(),
+            attestation_key: self.get_credential_signer()?.signer().public(),
+            delegation_key: self.get_delegation_signer()?.signer().public(),
+            key_agreement_key: self.get_nacl_secret_key()?.public_key(),
+        })
+    }
+
     pub fn get_nacl_secret_key(&self) -> anyhow::Result<SecretKey> {
         let raw_key = hex::decode(self.session.nacl_secret_key.trim_start_matches("0x"))?;
         SecretKey::from_slice(&raw_key).ok_or(anyhow::anyhow!("Generating secret key failed"))
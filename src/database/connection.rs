@@ -26,3 +26,12 @@ pub async fn connect(database_url: &str) -> anyhow::Result<Pool<Postgres>> {
         .context("Creating database connection failed")?;
     Ok(pool)
 }
+
+/// Version of the newest migration which is embedded into the binary.
+pub fn latest_migration_version() -> Option<i64> {
+    sqlx::migrate!()
+        .migrations
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+}
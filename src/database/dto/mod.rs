@@ -1,8 +1,8 @@
 mod attestation_requests;
 mod credential_api;
 mod query;
-mod utils;
 
 pub use attestation_requests::*;
+pub use attester_api::dto::*;
 pub use credential_api::*;
 pub use query::*;
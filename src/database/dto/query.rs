@@ -1,3 +1,5 @@
+use attester_api::dto::Query;
+
 #[derive(serde::Deserialize, Clone)]
 pub struct Pagination {
     pub offset: Option<[u32; 2]>,
@@ -5,13 +7,6 @@ pub struct Pagination {
     pub filter: Option<String>,
 }
 
-#[derive(serde::Deserialize, Clone)]
-pub struct Query {
-    pub range: Option<String>,
-    pub sort: Option<String>,
-    pub filter: Option<String>,
-}
-
 impl From<Query> for Pagination {
     fn from(value: Query) -> Self {
         Pagination {
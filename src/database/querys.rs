@@ -1,10 +1,13 @@
-use sqlx::{postgres::PgQueryResult, PgPool, Postgres, QueryBuilder};
+use sqlx::{postgres::PgQueryResult, PgExecutor, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use crate::database::dto::{
-    AttestationCreatedOverTime, AttestationKPIs, AttestationResponse, Credential, Pagination,
-    Session, TxState,
+    AcceptedQuote, Assignment, AttestationCreatedOverTime, AttestationKPIs, AttestationMessage,
+    AttestationResponse, AttestationState, AttestationStateCount, AttestationTransition, Comment,
+    Credential, Evidence, KpiFilter, Pagination, Review, ReviewDecision, Revision, Session,
+    TimeToApproval, TxState, VerificationRequest,
 };
+use crate::error::AppError;
 
 pub async fn get_attestation_request_by_id(
     attestation_request_id: &Uuid,
@@ -12,7 +15,7 @@ pub async fn get_attestation_request_by_id(
 ) -> Result<AttestationResponse, sqlx::Error> {
     sqlx::query_as!(
         AttestationResponse,
-        r#"SELECT id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, ctype_hash, credential, claimer, marked_approve, tx_state as "tx_state: TxState"
+        r#"SELECT id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, credential, claimer, marked_approve, tx_state as "tx_state: TxState", status as "status: AttestationState"
         FROM attestation_requests WHERE id = $1 AND deleted_at is NULL"#,
         attestation_request_id,
     )
@@ -92,7 +95,7 @@ pub async fn delete_attestation_request(
     db_executor: &PgPool,
 ) -> Result<PgQueryResult, sqlx::Error> {
     sqlx::query!(
-        "UPDATE attestation_requests SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        "UPDATE attestation_requests SET deleted_at = NOW(), status = 'Deleted' WHERE id = $1 AND status NOT IN ('Approved', 'Deleted')",
         attestation_id
     )
     .execute(db_executor)
@@ -107,49 +110,81 @@ pub async fn insert_attestation_request(
     let ctype_hash = credential.claim.ctype_hash.clone();
     sqlx::query_as!(
         AttestationResponse,
-        r#"INSERT INTO attestation_requests (ctype_hash, claimer, credential) VALUES ($1, $2, $3) 
-        RETURNING  id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, ctype_hash, credential, claimer, marked_approve, tx_state as "tx_state: TxState""#,
+        r#"INSERT INTO attestation_requests (ctype_hash, claimer, credential, delegation_id) VALUES ($1, $2, $3, $4) 
+        RETURNING  id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, credential, claimer, marked_approve, tx_state as "tx_state: TxState", status as "status: AttestationState""#,
         ctype_hash,
         claimer,
-        serde_json::json!(credential)
+        serde_json::json!(credential),
+        credential.delegation_id
     )
     .fetch_one(db_executor)
     .await
 }
 
-pub async fn can_approve_attestation_tx(
+/// Locks the attestation request until the transaction ends, so that concurrent transitions are
+/// checked against its latest state.
+async fn get_attestation_request_tx(
     attestation_request_id: &Uuid,
     tx: &mut sqlx::Transaction<'_, Postgres>,
 ) -> Result<AttestationResponse, sqlx::Error> {
     sqlx::query_as!(
         AttestationResponse,
-        r#"SELECT id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState" 
-        FROM attestation_requests WHERE id = $1 AND approved = false AND revoked = false AND deleted_at IS NULL"#,
+        r#"SELECT id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState", status as "status: AttestationState" 
+        FROM attestation_requests WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"#,
         attestation_request_id
     )
     .fetch_one(&mut **tx)
     .await
 }
 
+pub async fn can_approve_attestation_tx(
+    attestation_request_id: &Uuid,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<AttestationResponse, AppError> {
+    let attestation = get_attestation_request_tx(attestation_request_id, tx).await?;
+    attestation.check_transition(AttestationTransition::Approve)?;
+    Ok(attestation)
+}
+
+/// Locks the attestation request, if it was marked as approved and can be attested once the
+/// claimer requests the attestation.
+pub async fn can_attest_marked_approved_tx(
+    attestation_request_id: &Uuid,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<AttestationResponse, AppError> {
+    let attestation = get_attestation_request_tx(attestation_request_id, tx).await?;
+    if attestation.status != AttestationState::MarkedApproved {
+        Err(AppError::InvalidTransition {
+            state: attestation.status,
+            transition: AttestationTransition::Approve,
+        })?
+    }
+    Ok(attestation)
+}
+
+/// Affects no row if a transaction of the attestation request is already in flight.
 pub async fn mark_attestation_request_in_flight(
     attestation_request_id: &Uuid,
-    db_executor: &PgPool,
+    db_executor: impl PgExecutor<'_>,
 ) -> Result<PgQueryResult, sqlx::Error> {
     sqlx::query!(
-        "UPDATE attestation_requests SET tx_state = 'InFlight' WHERE id = $1",
+        "UPDATE attestation_requests SET tx_state = 'InFlight', status = 'InFlight' WHERE id = $1 AND status <> 'InFlight'",
         attestation_request_id
     )
     .execute(db_executor)
     .await
 }
 
+/// Returns the attestation request to the state it had before its transaction was submitted.
 pub async fn record_attestation_request_failed(
     attestation_request_id: &Uuid,
+    previous_state: AttestationState,
     tx: &mut sqlx::Transaction<'_, Postgres>,
 ) -> Result<PgQueryResult, sqlx::Error> {
     sqlx::query!(
-        "UPDATE attestation_requests SET tx_state = 'Failed' WHERE id = $1",
-        attestation_request_id
+        "UPDATE attestation_requests SET tx_state = 'Failed', status = $2 WHERE id = $1",
+        attestation_request_id,
+        previous_state as AttestationState
     )
     .execute(&mut **tx)
     .await
@@ -160,7 +195,7 @@ pub async fn approve_attestation_request(
     tx: &mut sqlx::Transaction<'_, Postgres>,
 ) -> Result<PgQueryResult, sqlx::Error> {
     sqlx::query!(
-        "UPDATE attestation_requests SET approved = true, tx_state = 'Succeeded', approved_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        "UPDATE attestation_requests SET approved = true, tx_state = 'Succeeded', approved_at = NOW(), status = 'Approved' WHERE id = $1 AND deleted_at IS NULL",
         attestation_request_id
     )
     .execute(&mut **tx)
@@ -170,65 +205,388 @@ pub async fn approve_attestation_request(
 pub async fn can_revoke_attestation(
     attestation_request_id: &Uuid,
     tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<AttestationResponse, AppError> {
+    let attestation = get_attestation_request_tx(attestation_request_id, tx).await?;
+    attestation.check_transition(AttestationTransition::Revoke)?;
+    Ok(attestation)
+}
+
+pub async fn revoke_attestation_request(
+    attestation_request_id: &Uuid,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "UPDATE attestation_requests SET revoked = true, revoked_at = NOW(), tx_state = 'Succeeded', status = 'Revoked' WHERE id = $1 AND deleted_at IS NULL",
+        attestation_request_id
+    )
+    .execute(&mut **tx)
+    .await
+}
+
+pub async fn reject_attestation_request(
+    attestation_request_id: &Uuid,
+    reviewer: &str,
+    reason: &str,
+    db_executor: impl PgExecutor<'_>,
+) -> Result<AttestationResponse, sqlx::Error> {
+    sqlx::query_as!(
+        AttestationResponse,
+        r#"UPDATE attestation_requests SET rejected = true, rejected_at = NOW(), rejected_by = $2, rejection_reason = $3, status = 'Rejected'
+        WHERE id = $1 AND status IN ('Pending', 'ChangesRequested')
+        RETURNING id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState", status as "status: AttestationState""#,
+        attestation_request_id,
+        reviewer,
+        reason
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn request_attestation_changes(
+    attestation_request_id: &Uuid,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
 ) -> Result<AttestationResponse, sqlx::Error> {
     sqlx::query_as!(
         AttestationResponse,
-        r#"SELECT id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState" 
-        FROM attestation_requests WHERE id = $1 AND approved = true AND revoked = false AND deleted_at IS NULL"#,
+        r#"UPDATE attestation_requests SET changes_requested = true, changes_requested_at = NOW(), status = 'ChangesRequested'
+        WHERE id = $1 AND status = 'Pending'
+        RETURNING id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState", status as "status: AttestationState""#,
         attestation_request_id
     )
     .fetch_one(&mut **tx)
     .await
 }
 
-pub async fn revoke_attestation_request(
+/// Replaces the credential of an attestation request with changes requested.
+/// The previous credential is kept as a new revision.
+pub async fn update_attestation_request_credential(
     attestation_request_id: &Uuid,
+    credential: &Credential,
     tx: &mut sqlx::Transaction<'_, Postgres>,
-) -> Result<PgQueryResult, sqlx::Error> {
+) -> Result<AttestationResponse, AppError> {
+    let attestation = get_attestation_request_tx(attestation_request_id, tx).await?;
+    attestation.check_transition(AttestationTransition::UpdateCredential)?;
+
     sqlx::query!(
-        "UPDATE attestation_requests SET revoked = true, revoked_at = NOW(), tx_state = 'Succeeded' WHERE id = $1 AND deleted_at IS NULL",
+        "INSERT INTO attestation_request_revisions (attestation_request_id, revision, credential)
+        SELECT id, (SELECT COALESCE(MAX(revision), 0) + 1 FROM attestation_request_revisions WHERE attestation_request_id = $1), credential
+        FROM attestation_requests WHERE id = $1",
         attestation_request_id
     )
     .execute(&mut **tx)
+    .await?;
+
+    let updated = sqlx::query_as!(
+        AttestationResponse,
+        r#"UPDATE attestation_requests SET credential = $2, delegation_id = $3, changes_requested = false, status = 'Pending'
+        WHERE id = $1
+        RETURNING id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState", status as "status: AttestationState""#,
+        attestation_request_id,
+        serde_json::json!(credential),
+        credential.delegation_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(updated)
+}
+
+pub async fn get_attestation_request_revisions(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<Vec<Revision>, sqlx::Error> {
+    sqlx::query_as!(
+        Revision,
+        "SELECT * FROM attestation_request_revisions WHERE attestation_request_id = $1 ORDER BY revision ASC",
+        attestation_request_id
+    )
+    .fetch_all(db_executor)
+    .await
+}
+
+pub async fn insert_comment(
+    attestation_request_id: &Uuid,
+    author: &str,
+    message: &str,
+    db_executor: impl PgExecutor<'_>,
+) -> Result<Comment, sqlx::Error> {
+    sqlx::query_as!(
+        Comment,
+        "INSERT INTO attestation_request_comments (attestation_request_id, author, message) VALUES ($1, $2, $3) RETURNING *",
+        attestation_request_id,
+        author,
+        message
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn get_comments(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<Vec<Comment>, sqlx::Error> {
+    sqlx::query_as!(
+        Comment,
+        "SELECT * FROM attestation_request_comments WHERE attestation_request_id = $1 ORDER BY created_at ASC",
+        attestation_request_id
+    )
+    .fetch_all(db_executor)
+    .await
+}
+
+pub async fn assign_reviewer(
+    attestation_request_id: &Uuid,
+    reviewer: &str,
+    assigned_by: &str,
+    db_executor: &PgPool,
+) -> Result<Assignment, sqlx::Error> {
+    sqlx::query_as!(
+        Assignment,
+        "INSERT INTO attestation_request_assignments (attestation_request_id, reviewer, assigned_by) VALUES ($1, $2, $3)
+        ON CONFLICT (attestation_request_id, reviewer) DO UPDATE SET assigned_by = $3, assigned_at = NOW()
+        RETURNING *",
+        attestation_request_id,
+        reviewer,
+        assigned_by
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn get_assignments(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<Vec<Assignment>, sqlx::Error> {
+    sqlx::query_as!(
+        Assignment,
+        "SELECT * FROM attestation_request_assignments WHERE attestation_request_id = $1 ORDER BY assigned_at ASC",
+        attestation_request_id
+    )
+    .fetch_all(db_executor)
+    .await
+}
+
+pub async fn insert_review(
+    attestation_request_id: &Uuid,
+    reviewer: &str,
+    decision: ReviewDecision,
+    db_executor: impl PgExecutor<'_>,
+) -> Result<Review, sqlx::Error> {
+    sqlx::query_as!(
+        Review,
+        r#"INSERT INTO attestation_request_reviews (attestation_request_id, reviewer, decision) VALUES ($1, $2, $3)
+        RETURNING id, attestation_request_id, reviewer, decision as "decision: ReviewDecision", created_at"#,
+        attestation_request_id,
+        reviewer,
+        decision as ReviewDecision
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn get_reviews(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<Vec<Review>, sqlx::Error> {
+    sqlx::query_as!(
+        Review,
+        r#"SELECT id, attestation_request_id, reviewer, decision as "decision: ReviewDecision", created_at
+        FROM attestation_request_reviews WHERE attestation_request_id = $1 ORDER BY created_at ASC"#,
+        attestation_request_id
+    )
+    .fetch_all(db_executor)
+    .await
+}
+
+/// Counts the distinct reviewers who approved the current revision of the credential.
+/// If a reviewer is given, only the approvals of this reviewer are counted.
+pub async fn count_approvals(
+    attestation_request_id: &Uuid,
+    reviewer: Option<&str>,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT COUNT(DISTINCT reviewer) FROM attestation_request_reviews
+        WHERE attestation_request_id = $1 AND decision = 'Approved' AND ($2::VARCHAR IS NULL OR reviewer = $2)
+        AND created_at > COALESCE((SELECT MAX(created_at) FROM attestation_request_revisions WHERE attestation_request_id = $1), '-infinity')",
+        attestation_request_id,
+        reviewer
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map(|count| count.unwrap_or(0))
+}
+
+pub async fn insert_evidence(
+    attestation_request_id: &Uuid,
+    file_name: &str,
+    content_type: &str,
+    size: i64,
+    uploaded_by: &str,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<Evidence, sqlx::Error> {
+    sqlx::query_as!(
+        Evidence,
+        "INSERT INTO evidences (attestation_request_id, file_name, content_type, size, uploaded_by) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        attestation_request_id,
+        file_name,
+        content_type,
+        size,
+        uploaded_by
+    )
+    .fetch_one(&mut **tx)
+    .await
+}
+
+pub async fn get_evidences(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<Vec<Evidence>, sqlx::Error> {
+    sqlx::query_as!(
+        Evidence,
+        "SELECT * FROM evidences WHERE attestation_request_id = $1 ORDER BY created_at ASC",
+        attestation_request_id
+    )
+    .fetch_all(db_executor)
+    .await
+}
+
+pub async fn get_evidence(
+    attestation_request_id: &Uuid,
+    evidence_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<Evidence, sqlx::Error> {
+    sqlx::query_as!(
+        Evidence,
+        "SELECT * FROM evidences WHERE id = $1 AND attestation_request_id = $2 AND purged_at IS NULL",
+        evidence_id,
+        attestation_request_id
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn mark_evidence_purged(
+    evidence_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "UPDATE evidences SET purged_at = NOW() WHERE id = $1",
+        evidence_id
+    )
+    .execute(db_executor)
+    .await
+}
+
+pub async fn request_attestation_revocation(
+    attestation_request_id: &Uuid,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<AttestationResponse, AppError> {
+    let attestation = get_attestation_request_tx(attestation_request_id, tx).await?;
+    attestation.check_transition(AttestationTransition::RequestRevocation)?;
+    if attestation.revocation_requested_at.is_some() {
+        Err(AppError::Conflict("The revocation is already requested"))?
+    }
+
+    let updated = sqlx::query_as!(
+        AttestationResponse,
+        r#"UPDATE attestation_requests SET revocation_requested_at = NOW()
+        WHERE id = $1
+        RETURNING id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState", status as "status: AttestationState""#,
+        attestation_request_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(updated)
+}
+
+pub async fn get_revocation_requests(
+    db_executor: &PgPool,
+) -> Result<Vec<AttestationResponse>, sqlx::Error> {
+    sqlx::query_as!(
+        AttestationResponse,
+        r#"SELECT id, approved, revoked, created_at, deleted_at,  approved_at, revoked_at, revocation_requested_at, rejected, rejected_at, rejected_by, rejection_reason, changes_requested, changes_requested_at, delegation_id, ctype_hash, marked_approve, credential, claimer, tx_state as "tx_state: TxState", status as "status: AttestationState"
+        FROM attestation_requests WHERE revocation_requested_at IS NOT NULL AND status IN ('Approved', 'InFlight')
+        ORDER BY revocation_requested_at ASC"#
+    )
+    .fetch_all(db_executor)
     .await
 }
 
-pub async fn attestation_requests_kpis(pool: &PgPool) -> Result<AttestationKPIs, sqlx::Error> {
+pub async fn attestation_requests_kpis(
+    filter: &KpiFilter,
+    pool: &PgPool,
+) -> Result<AttestationKPIs, sqlx::Error> {
     let attestations_created_over_time = sqlx::query_as!(
         AttestationCreatedOverTime,
-        "SELECT date_trunc('day', created_at) AS date, COUNT(*) AS total_attestations_created
+        "SELECT date_trunc($1, created_at) AS date, COUNT(*) AS total_attestations_created
          FROM attestation_requests
+         WHERE ($2::DATE IS NULL OR created_at >= $2) AND ($3::DATE IS NULL OR created_at < $3 + 1)
+         AND ($4::VARCHAR IS NULL OR LOWER(ctype_hash) = LOWER($4))
          GROUP BY date
          ORDER BY date;",
+        filter.bucket.as_str(),
+        filter.from,
+        filter.to,
+        filter.ctype_hash,
     )
     .fetch_all(pool)
     .await?;
 
-    let attestations_not_approved = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM attestation_requests WHERE approved = FALSE AND deleted_at IS NULL;"
+    let counts = sqlx::query!(
+        r#"SELECT
+            COUNT(*) FILTER (WHERE status IN ('Pending', 'ChangesRequested', 'MarkedApproved')) AS "not_approved!",
+            COUNT(*) FILTER (WHERE status IN ('Approved', 'Revoked')) AS "approved!",
+            COUNT(*) FILTER (WHERE status = 'Revoked') AS "revoked!",
+            COUNT(*) FILTER (WHERE status = 'Rejected') AS "rejected!",
+            COUNT(*) FILTER (WHERE status <> 'Deleted' AND tx_state = 'Failed') AS "failed!",
+            COUNT(DISTINCT claimer) FILTER (WHERE status <> 'Deleted') AS "total_claimers!",
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM approved_at - created_at))
+                FILTER (WHERE status IN ('Approved', 'Revoked')) AS median_time_to_approval,
+            percentile_cont(0.95) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM approved_at - created_at))
+                FILTER (WHERE status IN ('Approved', 'Revoked')) AS p95_time_to_approval
+        FROM attestation_requests
+        WHERE ($1::DATE IS NULL OR created_at >= $1) AND ($2::DATE IS NULL OR created_at < $2 + 1)
+        AND ($3::VARCHAR IS NULL OR LOWER(ctype_hash) = LOWER($3))"#,
+        filter.from,
+        filter.to,
+        filter.ctype_hash,
     )
     .fetch_one(pool)
-    .await
-    .map_or(0, |count| count.unwrap());
+    .await?;
 
-    let attestations_revoked = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM attestation_requests WHERE revoked = TRUE AND deleted_at IS NULL;"
+    // The failure rate includes failed transactions of requests which were retried successfully.
+    let txs = sqlx::query!(
+        r#"SELECT
+            COUNT(*) FILTER (WHERE t.tx_state = 'Failed') AS "failed!",
+            COUNT(*) AS "finished!"
+        FROM attestation_transactions t
+        JOIN attestation_requests r ON r.id = t.attestation_request_id
+        WHERE ($1::DATE IS NULL OR r.created_at >= $1) AND ($2::DATE IS NULL OR r.created_at < $2 + 1)
+        AND ($3::VARCHAR IS NULL OR LOWER(r.ctype_hash) = LOWER($3))"#,
+        filter.from,
+        filter.to,
+        filter.ctype_hash,
     )
     .fetch_one(pool)
-    .await
-    .map_or(0, |count| count.unwrap());
+    .await?;
 
-    let total_claimers =
-        sqlx::query_scalar!("SELECT COUNT(DISTINCT claimer) FROM attestation_requests;")
-            .fetch_one(pool)
-            .await
-            .map_or(0, |count| count.unwrap());
+    let tx_failure_rate = (txs.finished > 0).then(|| txs.failed as f64 / txs.finished as f64);
 
     Ok(AttestationKPIs {
         attestations_created_over_time,
-        attestations_not_approved,
-        attestations_revoked,
-        total_claimers,
+        attestations_not_approved: counts.not_approved,
+        attestations_approved: counts.approved,
+        attestations_revoked: counts.revoked,
+        attestations_rejected: counts.rejected,
+        attestations_failed: counts.failed,
+        total_claimers: counts.total_claimers,
+        time_to_approval: TimeToApproval {
+            median: counts.median_time_to_approval,
+            p95: counts.p95_time_to_approval,
+        },
+        tx_failure_rate,
     })
 }
 
@@ -261,14 +619,14 @@ pub async fn update_session(
 }
 
 pub async fn mark_attestation_approve(
-    pool: &PgPool,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
     attestation_request_id: &Uuid,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
-        "UPDATE attestation_requests SET marked_approve = true, approved_at = NOW() WHERE id = $1",
+        "UPDATE attestation_requests SET marked_approve = true, approved_at = NOW(), status = 'MarkedApproved' WHERE id = $1 AND status IN ('Pending', 'MarkedApproved')",
         attestation_request_id
     )
-    .execute(pool)
+    .execute(&mut **tx)
     .await?;
     Ok(())
 }
@@ -278,3 +636,187 @@ pub async fn remove_session(pool: &PgPool, id: &Uuid) -> Result<PgQueryResult, s
         .execute(pool)
         .await
 }
+
+pub async fn upsert_quote_agreement(
+    attestation_request_id: &Uuid,
+    agreement: &serde_json::Value,
+    db_executor: &PgPool,
+) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO quote_agreements (attestation_request_id, agreement) VALUES ($1, $2)
+        ON CONFLICT (attestation_request_id) DO UPDATE SET agreement = $2, created_at = NOW()
+        WHERE quote_agreements.paid_at IS NULL",
+        attestation_request_id,
+        agreement
+    )
+    .execute(db_executor)
+    .await
+}
+
+pub async fn get_quote_agreement(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<AcceptedQuote, sqlx::Error> {
+    sqlx::query_as!(
+        AcceptedQuote,
+        "SELECT * FROM quote_agreements WHERE attestation_request_id = $1",
+        attestation_request_id
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn mark_quote_paid(
+    attestation_request_id: &Uuid,
+    block_hash: &str,
+    tx_hash: &str,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "UPDATE quote_agreements SET payment_block_hash = $2, payment_tx_hash = $3, paid_at = NOW()
+        WHERE attestation_request_id = $1 AND paid_at IS NULL
+        AND NOT EXISTS (SELECT 1 FROM quote_agreements WHERE payment_tx_hash = $3)",
+        attestation_request_id,
+        block_hash,
+        tx_hash
+    )
+    .execute(&mut **tx)
+    .await
+}
+
+pub async fn queue_attestation_message(
+    attestation_request_id: &Uuid,
+    content: &serde_json::Value,
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO attestation_messages (attestation_request_id, content) VALUES ($1, $2)
+        ON CONFLICT (attestation_request_id) DO UPDATE SET content = $2, created_at = NOW(), delivered_at = NULL",
+        attestation_request_id,
+        content
+    )
+    .execute(&mut **tx)
+    .await
+}
+
+pub async fn get_attestation_message(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<AttestationMessage, sqlx::Error> {
+    sqlx::query_as!(
+        AttestationMessage,
+        "SELECT * FROM attestation_messages WHERE attestation_request_id = $1",
+        attestation_request_id
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn mark_attestation_message_delivered(
+    attestation_request_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<PgQueryResult, sqlx::Error> {
+    sqlx::query!(
+        "UPDATE attestation_messages SET delivered_at = NOW() WHERE attestation_request_id = $1",
+        attestation_request_id
+    )
+    .execute(db_executor)
+    .await
+}
+
+pub async fn insert_verification_request(
+    session_id: &Uuid,
+    requested_by: &str,
+    ctypes: &serde_json::Value,
+    challenge: &str,
+    db_executor: &PgPool,
+) -> Result<VerificationRequest, sqlx::Error> {
+    sqlx::query_as!(
+        VerificationRequest,
+        "INSERT INTO verification_requests (session_id, requested_by, ctypes, challenge) VALUES ($1, $2, $3, $4) RETURNING *",
+        session_id,
+        requested_by,
+        ctypes,
+        challenge
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn get_verification_request(
+    id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<VerificationRequest, sqlx::Error> {
+    sqlx::query_as!(
+        VerificationRequest,
+        "SELECT * FROM verification_requests WHERE id = $1",
+        id
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn get_open_verification_request(
+    session_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<VerificationRequest, sqlx::Error> {
+    sqlx::query_as!(
+        VerificationRequest,
+        "SELECT * FROM verification_requests WHERE session_id = $1 AND verified_at IS NULL
+        ORDER BY created_at DESC LIMIT 1",
+        session_id
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn complete_verification_request(
+    id: &Uuid,
+    verified_credentials: &serde_json::Value,
+    db_executor: &PgPool,
+) -> Result<VerificationRequest, sqlx::Error> {
+    sqlx::query_as!(
+        VerificationRequest,
+        "UPDATE verification_requests SET verified_at = NOW(), verified_credentials = $2
+        WHERE id = $1 AND verified_at IS NULL RETURNING *",
+        id,
+        verified_credentials
+    )
+    .fetch_one(db_executor)
+    .await
+}
+
+pub async fn get_migration_version(db_executor: &PgPool) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(db_executor)
+        .await
+}
+
+pub async fn attestation_request_state_counts(
+    db_executor: &PgPool,
+) -> Result<Vec<AttestationStateCount>, sqlx::Error> {
+    sqlx::query_as!(
+        AttestationStateCount,
+        r#"SELECT ctype_hash, lower(regexp_replace(status::text, '([a-z])([A-Z])', '\1_\2', 'g')) as "state!", COUNT(*) as "count!"
+        FROM attestation_requests WHERE status <> 'Deleted' GROUP BY ctype_hash, status"#
+    )
+    .fetch_all(db_executor)
+    .await
+}
+
+/// Returns the number of requests with a transaction in flight and the number of undelivered attestation messages.
+pub async fn get_queue_depths(db_executor: &PgPool) -> Result<(i64, i64), sqlx::Error> {
+    let in_flight = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM attestation_requests WHERE status = 'InFlight'"#
+    )
+    .fetch_one(db_executor)
+    .await?;
+
+    let undelivered = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM attestation_messages WHERE delivered_at IS NULL"#
+    )
+    .fetch_one(db_executor)
+    .await?;
+
+    Ok((in_flight, undelivered))
+}
@@ -1,14 +1,27 @@
+use actix_web::{http::StatusCode, ResponseError};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::database::dto::{Credential, Pagination, Query, TxState};
+use crate::database::dto::{
+    AttestationState, AttestationTransition, Credential, KpiBucket, KpiFilter, Pagination, Query,
+    ReviewDecision, TxState,
+};
 use crate::database::querys::{
-    approve_attestation_request, attestation_requests_kpis, can_approve_attestation_tx,
-    can_revoke_attestation, construct_query, delete_attestation_request,
-    get_attestation_request_by_id, get_attestation_requests, get_attestations_count,
-    insert_attestation_request, mark_attestation_request_in_flight,
-    record_attestation_request_failed, revoke_attestation_request,
+    approve_attestation_request, assign_reviewer, attestation_request_state_counts,
+    attestation_requests_kpis, can_approve_attestation_tx, can_revoke_attestation,
+    complete_verification_request, construct_query, count_approvals, delete_attestation_request,
+    get_assignments, get_attestation_message, get_attestation_request_by_id,
+    get_attestation_request_revisions, get_attestation_requests, get_attestations_count,
+    get_comments, get_evidence, get_evidences, get_open_verification_request, get_queue_depths,
+    get_quote_agreement, get_reviews, get_revocation_requests, get_verification_request,
+    insert_attestation_request, insert_comment, insert_evidence, insert_review,
+    insert_verification_request, mark_attestation_message_delivered,
+    mark_attestation_request_in_flight, mark_evidence_purged, mark_quote_paid,
+    queue_attestation_message, record_attestation_request_failed, reject_attestation_request,
+    request_attestation_changes, request_attestation_revocation, revoke_attestation_request,
+    update_attestation_request_credential, upsert_quote_agreement,
 };
+use crate::error::AppError;
 
 fn get_default_attestation_request() -> Credential {
     // Create a default Credential object for testing.
@@ -43,6 +56,29 @@ fn get_default_attestation_request() -> Credential {
     serde_json::from_value::<Credential>(credential_json.clone()).unwrap()
 }
 
+/// Approves the attestation request the same way as the approval route, i.e. with the transaction in flight first.
+async fn approve(attestation_request_id: &Uuid, db_executor: &PgPool) {
+    mark_attestation_request_in_flight(attestation_request_id, db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    approve_attestation_request(attestation_request_id, &mut tx)
+        .await
+        .expect("Approval should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+}
+
+async fn revoke(attestation_request_id: &Uuid, db_executor: &PgPool) {
+    mark_attestation_request_in_flight(attestation_request_id, db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    revoke_attestation_request(attestation_request_id, &mut tx)
+        .await
+        .expect("Revocation should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+}
+
 #[sqlx::test]
 async fn test_insert_attestation_request_valid(db_executor: PgPool) {
     // Arrange: Create a default attestation request.
@@ -330,13 +366,7 @@ async fn test_can_approve_attestation_tx_already_approved(db_executor: PgPool) {
         .expect("Insertion failed");
 
     // Mark the attestation request as already approved.
-    sqlx::query!(
-        "UPDATE attestation_requests SET approved = true WHERE id = $1",
-        inserted_request.id
-    )
-    .execute(&db_executor)
-    .await
-    .expect("Update of attestation should not fail.");
+    approve(&inserted_request.id, &db_executor).await;
 
     // Act: Try to approve the already approved attestation request.
     let result = can_approve_attestation_tx(&inserted_request.id, &mut tx).await;
@@ -345,6 +375,57 @@ async fn test_can_approve_attestation_tx_already_approved(db_executor: PgPool) {
     assert!(result.is_err());
 }
 
+#[sqlx::test]
+async fn test_can_approve_attestation_tx_in_flight(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request with a transaction in flight.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    mark_attestation_request_in_flight(&inserted_request.id, &db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+
+    // Act: Try to approve the attestation request.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result = can_approve_attestation_tx(&inserted_request.id, &mut tx).await;
+
+    // Assert: Check that the approval conflicts with the current state.
+    assert!(matches!(
+        result,
+        Err(AppError::InvalidTransition {
+            state: AttestationState::InFlight,
+            transition: AttestationTransition::Approve,
+        })
+    ));
+}
+
+#[sqlx::test]
+async fn test_attestation_status_transitions(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and approve it.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    assert_eq!(inserted_request.status, AttestationState::Pending);
+    approve(&inserted_request.id, &db_executor).await;
+
+    // Act: Revoke the attestation without submitting the revocation first.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result = revoke_attestation_request(&inserted_request.id, &mut tx).await;
+
+    // Assert: Check that the database rejects the transition and the request stays approved.
+    let error = AppError::from(result.expect_err("Revocation should fail"));
+    assert_eq!(error.status_code(), StatusCode::CONFLICT);
+    assert_eq!(error.code(), "invalid_state_transition");
+    tx.rollback().await.expect("Transaction rollback failed");
+
+    let attestation = get_attestation_request_by_id(&inserted_request.id, &db_executor)
+        .await
+        .expect("Attestation should exist");
+    assert_eq!(attestation.status, AttestationState::Approved);
+}
+
 #[sqlx::test]
 async fn test_record_attestation_request_failed(db_executor: PgPool) {
     // Arrange: Start a transaction and insert a default attestation request.
@@ -355,7 +436,9 @@ async fn test_record_attestation_request_failed(db_executor: PgPool) {
         .expect("Insertion failed");
 
     // Act: Record that the attestation request has failed and commit the transaction.
-    let result = record_attestation_request_failed(&inserted_request.id, &mut tx).await;
+    let result =
+        record_attestation_request_failed(&inserted_request.id, AttestationState::Pending, &mut tx)
+            .await;
 
     // Assert: Check that the result is successful and the attestation request is marked as failed.
     assert!(result.is_ok());
@@ -370,6 +453,7 @@ async fn test_record_attestation_request_failed(db_executor: PgPool) {
 
     // Check the state of the attestation request.
     assert_eq!(attestation.tx_state.unwrap(), TxState::Failed);
+    assert_eq!(attestation.status, AttestationState::Pending);
 }
 
 #[sqlx::test]
@@ -382,13 +466,7 @@ async fn test_can_revoke_attestation_valid(db_executor: PgPool) {
         .expect("Insertion failed");
 
     // Mark the request as approved, so it's eligible for revocation.
-    sqlx::query!(
-        "UPDATE attestation_requests SET approved = true WHERE id = $1",
-        inserted_request.id
-    )
-    .execute(&db_executor)
-    .await
-    .expect("Update of attestation should not fail.");
+    approve(&inserted_request.id, &db_executor).await;
 
     // Act: Try to revoke the approved attestation request.
     let result = can_revoke_attestation(&inserted_request.id, &mut tx).await;
@@ -406,13 +484,8 @@ async fn test_can_revoke_attestation_already_revoked(db_executor: PgPool) {
         .expect("Insertion failed");
 
     // Mark the request as both approved and revoked.
-    sqlx::query!(
-        "UPDATE attestation_requests SET approved = true, revoked = true WHERE id = $1",
-        inserted_request.id
-    )
-    .execute(&db_executor)
-    .await
-    .expect("Update of attestation should not fail.");
+    approve(&inserted_request.id, &db_executor).await;
+    revoke(&inserted_request.id, &db_executor).await;
 
     // Act: Try to revoke an already revoked attestation request.
     let result = can_revoke_attestation(&inserted_request.id, &mut tx).await;
@@ -452,10 +525,60 @@ async fn test_mark_attestation_request_in_flight_valid(db_executor: PgPool) {
     assert!(result.is_ok());
 }
 
+#[sqlx::test]
+async fn test_mark_attestation_request_in_flight_twice(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and mark it as in flight.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    mark_attestation_request_in_flight(&inserted_request.id, &db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+
+    // Act: Mark the attestation request as in flight again.
+    let result = mark_attestation_request_in_flight(&inserted_request.id, &db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+
+    // Assert: Check that the second transaction is not marked, since one is in flight already.
+    assert_eq!(result.rows_affected(), 0);
+}
+
+#[sqlx::test]
+async fn test_can_revoke_attestation_rejected(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and reject it.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    reject_attestation_request(
+        &inserted_request.id,
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH",
+        "Email is not verified",
+        &db_executor,
+    )
+    .await
+    .expect("Rejection failed");
+
+    // Act: Try to revoke the rejected attestation request.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result = can_revoke_attestation(&inserted_request.id, &mut tx).await;
+
+    // Assert: Check that the revocation conflicts with the current state.
+    assert!(matches!(
+        result,
+        Err(AppError::InvalidTransition {
+            state: AttestationState::Rejected,
+            transition: AttestationTransition::Revoke,
+        })
+    ));
+}
+
 #[sqlx::test]
 async fn test_attestation_requests_kpis_empty_db(db_executor: PgPool) {
     // Act: Get KPIs for an empty database.
-    let result = attestation_requests_kpis(&db_executor).await;
+    let result = attestation_requests_kpis(&KpiFilter::default(), &db_executor).await;
 
     // Assert: Check that the result is successful and KPIs are as expected.
     assert!(result.is_ok());
@@ -463,6 +586,7 @@ async fn test_attestation_requests_kpis_empty_db(db_executor: PgPool) {
     assert_eq!(kpis.attestations_created_over_time.len(), 0);
     assert_eq!(kpis.attestations_not_approved, 0);
     assert_eq!(kpis.attestations_revoked, 0);
+    assert_eq!(kpis.attestations_rejected, 0);
     assert_eq!(kpis.total_claimers, 0);
 }
 
@@ -475,7 +599,7 @@ async fn test_attestation_requests_kpis_with_data(db_executor: PgPool) {
         .expect("Insertion failed");
 
     // Act: Get KPIs for the database with data.
-    let result = attestation_requests_kpis(&db_executor).await;
+    let result = attestation_requests_kpis(&KpiFilter::default(), &db_executor).await;
 
     // Assert: Check that the result is successful and KPIs are as expected.
     assert!(result.is_ok());
@@ -483,6 +607,7 @@ async fn test_attestation_requests_kpis_with_data(db_executor: PgPool) {
     assert_eq!(kpis.attestations_created_over_time.len(), 1);
     assert_eq!(kpis.attestations_not_approved, 1);
     assert_eq!(kpis.attestations_revoked, 0);
+    assert_eq!(kpis.attestations_rejected, 0);
     assert_eq!(kpis.total_claimers, 1);
 }
 
@@ -494,6 +619,12 @@ async fn test_revoke_attestation_request_valid(db_executor: PgPool) {
         .await
         .expect("Insertion failed");
 
+    // Approve the request and submit the revocation, so it's eligible for revocation.
+    approve(&inserted_attestation.id, &db_executor).await;
+    mark_attestation_request_in_flight(&inserted_attestation.id, &db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+
     // Start a transaction.
     let mut tx = db_executor.begin().await.expect("Transaction start failed");
 
@@ -524,6 +655,11 @@ async fn test_approve_attestation_request_valid(db_executor: PgPool) {
         .await
         .expect("Insertion failed");
 
+    // Submit the attestation, so the request can be approved.
+    mark_attestation_request_in_flight(&inserted_attestation.id, &db_executor)
+        .await
+        .expect("Marking in flight should not fail");
+
     // Start a transaction.
     let mut tx = db_executor.begin().await.expect("Transaction start failed");
 
@@ -572,3 +708,680 @@ fn test_cast_query_to_pagination() {
     assert!(pagination2.offset.is_none());
     assert!(pagination2.sort.is_none());
 }
+
+#[sqlx::test]
+async fn test_delete_attestation_request_approved(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and mark it as approved.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    approve(&inserted_request.id, &db_executor).await;
+
+    // Act: Try to delete the approved attestation request.
+    let result = delete_attestation_request(&inserted_request.id, &db_executor)
+        .await
+        .expect("Delete query should not fail");
+
+    // Assert: Check that nothing was deleted and the attestation can still be fetched.
+    assert_eq!(result.rows_affected(), 0);
+    assert!(
+        get_attestation_request_by_id(&inserted_request.id, &db_executor)
+            .await
+            .is_ok()
+    );
+}
+
+#[sqlx::test]
+async fn test_request_attestation_revocation_valid(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and mark it as approved.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    approve(&inserted_request.id, &db_executor).await;
+
+    // Act: Request the revocation of the approved attestation.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result = request_attestation_revocation(&inserted_request.id, &mut tx).await;
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that the revocation is requested and listed for the reviewers.
+    assert!(result.is_ok());
+    assert!(result.unwrap().revocation_requested_at.is_some());
+
+    let revocation_requests = get_revocation_requests(&db_executor)
+        .await
+        .expect("Fetching revocation requests should not fail");
+    assert_eq!(revocation_requests.len(), 1);
+    assert_eq!(revocation_requests[0].id, inserted_request.id);
+
+    // Act: Request the revocation a second time.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let second_result = request_attestation_revocation(&inserted_request.id, &mut tx).await;
+
+    // Assert: Check that the revocation can only be requested once.
+    assert!(matches!(second_result, Err(AppError::Conflict(_))));
+}
+
+#[sqlx::test]
+async fn test_request_attestation_revocation_not_approved(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    // Act: Request the revocation of an attestation which is not on chain.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result = request_attestation_revocation(&inserted_request.id, &mut tx).await;
+    tx.rollback().await.expect("Transaction rollback failed");
+
+    // Assert: Check that the request conflicts with the current state and the queue stays empty.
+    assert!(matches!(
+        result,
+        Err(AppError::InvalidTransition {
+            state: AttestationState::Pending,
+            transition: AttestationTransition::RequestRevocation,
+        })
+    ));
+    let revocation_requests = get_revocation_requests(&db_executor)
+        .await
+        .expect("Fetching revocation requests should not fail");
+    assert!(revocation_requests.is_empty());
+}
+
+#[sqlx::test]
+async fn test_get_revocation_requests_after_revoke(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request with a requested revocation.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    approve(&inserted_request.id, &db_executor).await;
+    sqlx::query!(
+        "UPDATE attestation_requests SET revocation_requested_at = NOW() WHERE id = $1",
+        inserted_request.id
+    )
+    .execute(&db_executor)
+    .await
+    .expect("Update of attestation should not fail.");
+
+    // Act: Revoke the attestation request.
+    revoke(&inserted_request.id, &db_executor).await;
+
+    // Assert: Check that revoked attestations are removed from the queue.
+    let revocation_requests = get_revocation_requests(&db_executor)
+        .await
+        .expect("Fetching revocation requests should not fail");
+    assert!(revocation_requests.is_empty());
+}
+
+#[sqlx::test]
+async fn test_reject_attestation_request_valid(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    let reviewer = "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH";
+
+    // Act: Reject the attestation request.
+    let result = reject_attestation_request(
+        &inserted_request.id,
+        reviewer,
+        "Email is not verified",
+        &db_executor,
+    )
+    .await;
+
+    // Assert: Check that the rejection is stored together with the reviewer and the reason.
+    assert!(result.is_ok());
+    let rejected_attestation = get_attestation_request_by_id(&inserted_request.id, &db_executor)
+        .await
+        .expect("Attestation should exist");
+    assert!(rejected_attestation.rejected);
+    assert!(rejected_attestation.rejected_at.is_some());
+    assert_eq!(rejected_attestation.rejected_by.as_deref(), Some(reviewer));
+    assert_eq!(
+        rejected_attestation.rejection_reason.as_deref(),
+        Some("Email is not verified")
+    );
+
+    // Assert: Check that a rejected attestation can not be approved anymore.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let can_approve = can_approve_attestation_tx(&inserted_request.id, &mut tx).await;
+    assert!(can_approve.is_err());
+
+    // Assert: Check that the rejection is counted in the KPIs.
+    let kpis = attestation_requests_kpis(&KpiFilter::default(), &db_executor)
+        .await
+        .expect("KPIs retrieval failed");
+    assert_eq!(kpis.attestations_rejected, 1);
+    assert_eq!(kpis.attestations_not_approved, 0);
+}
+
+#[sqlx::test]
+async fn test_reject_attestation_request_already_approved(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and mark it as approved.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    approve(&inserted_request.id, &db_executor).await;
+
+    // Act: Try to reject the approved attestation request.
+    let result = reject_attestation_request(
+        &inserted_request.id,
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH",
+        "Too late",
+        &db_executor,
+    )
+    .await;
+
+    // Assert: Check that the result is an error (rejection is only possible before approval).
+    assert!(result.is_err());
+}
+
+#[sqlx::test]
+async fn test_request_attestation_changes_and_update_credential(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    let reviewer = "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH";
+
+    // Act: Request changes and leave a comment for the claimer.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result = request_attestation_changes(&inserted_request.id, &mut tx).await;
+    insert_comment(
+        &inserted_request.id,
+        reviewer,
+        "Please use your work email",
+        &mut *tx,
+    )
+    .await
+    .expect("Inserting comment should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that changes are requested and approval is blocked meanwhile.
+    assert!(result.is_ok());
+    assert!(result.unwrap().changes_requested);
+
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    assert!(can_approve_attestation_tx(&inserted_request.id, &mut tx)
+        .await
+        .is_err());
+    tx.rollback().await.expect("Transaction rollback failed");
+
+    let comments = get_comments(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching comments should not fail");
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].author, reviewer);
+
+    // Act: Submit an updated credential.
+    let mut updated_credential = credential.clone();
+    updated_credential.root_hash =
+        "0x81cca59b6c432a9f7bcfbd995b97aea2faca08727899ea6c3c05b512ef9c0288".to_string();
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let updated_attestation =
+        update_attestation_request_credential(&inserted_request.id, &updated_credential, &mut tx)
+            .await
+            .expect("Updating the credential should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that the credential is replaced and the previous one is kept as revision.
+    assert!(!updated_attestation.changes_requested);
+    assert_eq!(
+        updated_attestation.credential,
+        serde_json::json!(updated_credential)
+    );
+
+    let revisions = get_attestation_request_revisions(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching revisions should not fail");
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].revision, 1);
+    assert_eq!(revisions[0].credential, serde_json::json!(credential));
+}
+
+#[sqlx::test]
+async fn test_update_attestation_request_credential_without_change_request(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    // Act: Try to update the credential without a change request.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let result =
+        update_attestation_request_credential(&inserted_request.id, &credential, &mut tx).await;
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that the update conflicts with the current state and no revision is stored.
+    assert!(matches!(
+        result,
+        Err(AppError::InvalidTransition {
+            state: AttestationState::Pending,
+            transition: AttestationTransition::UpdateCredential,
+        })
+    ));
+    let revisions = get_attestation_request_revisions(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching revisions should not fail");
+    assert!(revisions.is_empty());
+}
+
+#[sqlx::test]
+async fn test_count_approvals_distinct_reviewers(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and assign two reviewers.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    let first_reviewer = "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH";
+    let second_reviewer = "did:kilt:4pnfkRn5UurBJTW92d9TaVLR2CqJdY4z5HPjrEbpGyBykare";
+
+    for reviewer in [first_reviewer, second_reviewer] {
+        assign_reviewer(&inserted_request.id, reviewer, reviewer, &db_executor)
+            .await
+            .expect("Assigning reviewer should not fail");
+    }
+
+    // Act: Approve twice by the first reviewer and once by the second reviewer.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    for reviewer in [first_reviewer, first_reviewer, second_reviewer] {
+        insert_review(
+            &inserted_request.id,
+            reviewer,
+            ReviewDecision::Approved,
+            &mut *tx,
+        )
+        .await
+        .expect("Inserting review should not fail");
+    }
+
+    // Assert: Check that only distinct reviewers are counted.
+    let approvals = count_approvals(&inserted_request.id, None, &mut tx)
+        .await
+        .expect("Counting approvals should not fail");
+    assert_eq!(approvals, 2);
+
+    let approvals_first_reviewer =
+        count_approvals(&inserted_request.id, Some(first_reviewer), &mut tx)
+            .await
+            .expect("Counting approvals should not fail");
+    assert_eq!(approvals_first_reviewer, 1);
+    tx.commit().await.expect("Transaction commit failed");
+
+    let assignments = get_assignments(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching assignments should not fail");
+    assert_eq!(assignments.len(), 2);
+
+    let reviews = get_reviews(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching reviews should not fail");
+    assert_eq!(reviews.len(), 3);
+}
+
+#[sqlx::test]
+async fn test_count_approvals_after_credential_update(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request and approve it.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    let reviewer = "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH";
+
+    insert_review(
+        &inserted_request.id,
+        reviewer,
+        ReviewDecision::Approved,
+        &db_executor,
+    )
+    .await
+    .expect("Inserting review should not fail");
+
+    // Act: Request changes and submit a new credential.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    request_attestation_changes(&inserted_request.id, &mut tx)
+        .await
+        .expect("Requesting changes should not fail");
+    update_attestation_request_credential(&inserted_request.id, &credential, &mut tx)
+        .await
+        .expect("Updating the credential should not fail");
+
+    // Assert: Check that approvals of the previous revision are not counted.
+    let approvals = count_approvals(&inserted_request.id, None, &mut tx)
+        .await
+        .expect("Counting approvals should not fail");
+    assert_eq!(approvals, 0);
+}
+
+#[sqlx::test]
+async fn test_insert_and_purge_evidence(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    // Act: Insert an evidence for the attestation request.
+    let mut tx = db_executor.begin().await.expect("Transaction start failed");
+    let evidence = insert_evidence(
+        &inserted_request.id,
+        "passport.pdf",
+        "application/pdf",
+        1024,
+        &credential.claim.owner,
+        &mut tx,
+    )
+    .await
+    .expect("Inserting evidence should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that the evidence is listed and can be fetched.
+    let evidences = get_evidences(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching evidences should not fail");
+    assert_eq!(evidences.len(), 1);
+    assert_eq!(evidences[0].file_name, "passport.pdf");
+    assert!(
+        get_evidence(&inserted_request.id, &evidence.id, &db_executor)
+            .await
+            .is_ok()
+    );
+
+    // Act: Mark the evidence as purged.
+    mark_evidence_purged(&evidence.id, &db_executor)
+        .await
+        .expect("Purging evidence should not fail");
+
+    // Assert: Check that purged evidences stay listed but can not be fetched anymore.
+    let evidences = get_evidences(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching evidences should not fail");
+    assert!(evidences[0].purged_at.is_some());
+    assert!(
+        get_evidence(&inserted_request.id, &evidence.id, &db_executor)
+            .await
+            .is_err()
+    );
+}
+
+#[sqlx::test]
+async fn test_mark_quote_paid(db_executor: PgPool) {
+    // Arrange: Insert two attestation requests with accepted quotes.
+    let credential = get_default_attestation_request();
+    let first_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Inserting attestation request should not fail");
+    let second_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Inserting attestation request should not fail");
+    let agreement = serde_json::json!({ "rootHash": credential.root_hash });
+    for request in [&first_request, &second_request] {
+        upsert_quote_agreement(&request.id, &agreement, &db_executor)
+            .await
+            .expect("Storing quote agreement should not fail");
+    }
+    let block_hash = "0x8e7d4f6bfb5b1e4e3b2e3c4b8b8c6bb2e5a51a46d4c6c7d8f6f2c2a0bd3e1f11";
+    let tx_hash = "0x4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e";
+
+    // Act: Pay the first quote.
+    let mut tx = db_executor.begin().await.expect("Transaction begin failed");
+    let result = mark_quote_paid(&first_request.id, block_hash, tx_hash, &mut tx)
+        .await
+        .expect("Marking quote as paid should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that the payment is stored.
+    assert_eq!(result.rows_affected(), 1);
+    let accepted_quote = get_quote_agreement(&first_request.id, &db_executor)
+        .await
+        .expect("Fetching quote agreement should not fail");
+    assert!(accepted_quote.paid_at.is_some());
+    assert_eq!(accepted_quote.payment_tx_hash.as_deref(), Some(tx_hash));
+
+    // Act: Reuse the same payment for the second quote.
+    let mut tx = db_executor.begin().await.expect("Transaction begin failed");
+    let result = mark_quote_paid(&second_request.id, block_hash, tx_hash, &mut tx)
+        .await
+        .expect("Marking quote as paid should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that a payment can only be used once.
+    assert_eq!(result.rows_affected(), 0);
+    let accepted_quote = get_quote_agreement(&second_request.id, &db_executor)
+        .await
+        .expect("Fetching quote agreement should not fail");
+    assert!(accepted_quote.paid_at.is_none());
+}
+
+#[sqlx::test]
+async fn test_queue_and_deliver_attestation_message(db_executor: PgPool) {
+    // Arrange: Insert a default attestation request.
+    let credential = get_default_attestation_request();
+    let inserted_request = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Inserting attestation request should not fail");
+    let content = serde_json::json!({ "claimHash": credential.root_hash, "revoked": false });
+
+    // Act: Queue the attestation message.
+    let mut tx = db_executor.begin().await.expect("Transaction begin failed");
+    queue_attestation_message(&inserted_request.id, &content, &mut tx)
+        .await
+        .expect("Queueing attestation message should not fail");
+    tx.commit().await.expect("Transaction commit failed");
+
+    // Assert: Check that the message waits for delivery.
+    let message = get_attestation_message(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching attestation message should not fail");
+    assert_eq!(message.content, content);
+    assert!(message.delivered_at.is_none());
+
+    // Act: Deliver the message.
+    mark_attestation_message_delivered(&inserted_request.id, &db_executor)
+        .await
+        .expect("Marking attestation message as delivered should not fail");
+
+    // Assert: Check that the delivery is recorded.
+    let message = get_attestation_message(&inserted_request.id, &db_executor)
+        .await
+        .expect("Fetching attestation message should not fail");
+    assert!(message.delivered_at.is_some());
+}
+
+#[sqlx::test]
+async fn test_verification_request_lifecycle(db_executor: PgPool) {
+    // Arrange: Create a verification request for a session.
+    let session_id = Uuid::new_v4();
+    let verifier = "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH";
+    let ctypes = serde_json::json!([{
+        "cTypeHash": "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac"
+    }]);
+    let inserted =
+        insert_verification_request(&session_id, verifier, &ctypes, "0x1234", &db_executor)
+            .await
+            .expect("Inserting verification request should not fail");
+
+    // Act: Look up the open request of the session.
+    let open = get_open_verification_request(&session_id, &db_executor)
+        .await
+        .expect("Fetching open verification request should not fail");
+
+    // Assert: Check that the request is open.
+    assert_eq!(open.id, inserted.id);
+    assert_eq!(open.ctypes, ctypes);
+    assert!(open.verified_at.is_none());
+
+    // Act: Complete the request.
+    let verified_credentials = serde_json::json!([{ "rootHash": "0x01" }]);
+    let completed =
+        complete_verification_request(&inserted.id, &verified_credentials, &db_executor)
+            .await
+            .expect("Completing verification request should not fail");
+
+    // Assert: Check that a completed request can neither be reopened nor completed again.
+    assert!(completed.verified_at.is_some());
+    assert_eq!(
+        completed.verified_credentials,
+        Some(verified_credentials.clone())
+    );
+    assert!(get_open_verification_request(&session_id, &db_executor)
+        .await
+        .is_err());
+    assert!(
+        complete_verification_request(&inserted.id, &verified_credentials, &db_executor)
+            .await
+            .is_err()
+    );
+    let fetched = get_verification_request(&inserted.id, &db_executor)
+        .await
+        .expect("Fetching verification request should not fail");
+    assert_eq!(fetched.requested_by, verifier);
+}
+
+#[sqlx::test]
+async fn test_attestation_request_state_counts(db_executor: PgPool) {
+    // Arrange: Insert two requests and reject one of them. One request is in flight.
+    let credential = get_default_attestation_request();
+    let pending = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Inserting attestation request should not fail");
+    let rejected = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Inserting attestation request should not fail");
+    reject_attestation_request(&rejected.id, "admin", "Invalid", &db_executor)
+        .await
+        .expect("Rejecting should not fail");
+    mark_attestation_request_in_flight(&pending.id, &db_executor)
+        .await
+        .expect("Marking request in flight should not fail");
+
+    // Act: Count the requests by state and read the queue depths.
+    let mut counts = attestation_request_state_counts(&db_executor)
+        .await
+        .expect("Counting should not fail");
+    counts.sort_by(|a, b| a.state.cmp(&b.state));
+    let (in_flight, undelivered) = get_queue_depths(&db_executor)
+        .await
+        .expect("Reading queue depths should not fail");
+
+    // Assert: Both states are counted for the CType of the credential.
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[0].state, "in_flight");
+    assert_eq!(counts[0].count, 1);
+    assert_eq!(counts[1].state, "rejected");
+    assert_eq!(counts[1].ctype_hash, credential.claim.ctype_hash);
+    assert_eq!(in_flight, 1);
+    assert_eq!(undelivered, 0);
+}
+
+#[sqlx::test]
+async fn test_attestation_requests_kpis_filtered(db_executor: PgPool) {
+    // Arrange: Insert an approved and a failed attestation request.
+    let credential = get_default_attestation_request();
+    let approved = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    let failed = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    approve(&approved.id, &db_executor).await;
+
+    let mut tx = db_executor.begin().await.expect("Starting tx failed");
+    record_attestation_request_failed(&failed.id, AttestationState::Pending, &mut tx)
+        .await
+        .expect("Recording the failure failed");
+    tx.commit().await.expect("Commit failed");
+
+    // Act: Get the KPIs of the CType and of another CType.
+    let filter = KpiFilter {
+        ctype_hash: Some(credential.claim.ctype_hash.to_uppercase()),
+        bucket: KpiBucket::Month,
+        ..Default::default()
+    };
+    let kpis = attestation_requests_kpis(&filter, &db_executor)
+        .await
+        .expect("KPIs retrieval failed");
+    let other_kpis = attestation_requests_kpis(
+        &KpiFilter {
+            ctype_hash: Some("0x00".to_string()),
+            ..Default::default()
+        },
+        &db_executor,
+    )
+    .await
+    .expect("KPIs retrieval failed");
+    let past_kpis = attestation_requests_kpis(
+        &KpiFilter {
+            to: chrono::NaiveDate::from_ymd_opt(2020, 1, 1),
+            ..Default::default()
+        },
+        &db_executor,
+    )
+    .await
+    .expect("KPIs retrieval failed");
+
+    // Assert: Check the breakdown, the approval latency and the failure rate.
+    assert_eq!(kpis.attestations_created_over_time.len(), 1);
+    assert_eq!(kpis.attestations_approved, 1);
+    assert_eq!(kpis.attestations_failed, 1);
+    assert_eq!(kpis.attestations_not_approved, 1);
+    assert!(kpis.time_to_approval.median.is_some());
+    assert!(kpis.time_to_approval.p95.is_some());
+    assert_eq!(kpis.tx_failure_rate, Some(0.5));
+    assert_eq!(other_kpis.attestations_approved, 0);
+    assert_eq!(other_kpis.tx_failure_rate, None);
+    assert_eq!(past_kpis.attestations_created_over_time.len(), 0);
+}
+
+#[sqlx::test]
+async fn test_attestation_requests_kpis_retried_tx(db_executor: PgPool) {
+    // Arrange: Fail the transaction of a request and approve it on the retry.
+    let credential = get_default_attestation_request();
+    let retried = insert_attestation_request(&credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+
+    let mut tx = db_executor.begin().await.expect("Starting tx failed");
+    record_attestation_request_failed(&retried.id, AttestationState::Pending, &mut tx)
+        .await
+        .expect("Recording the failure failed");
+    tx.commit().await.expect("Commit failed");
+
+    approve(&retried.id, &db_executor).await;
+
+    // Arrange: Delete the request of another claimer.
+    let mut other_credential = credential.clone();
+    other_credential.claim.owner =
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH".to_string();
+    let deleted = insert_attestation_request(&other_credential, &db_executor)
+        .await
+        .expect("Insertion failed");
+    delete_attestation_request(&deleted.id, &db_executor)
+        .await
+        .expect("Deletion failed");
+
+    // Act
+    let kpis = attestation_requests_kpis(&KpiFilter::default(), &db_executor)
+        .await
+        .expect("KPIs retrieval failed");
+
+    // Assert: Check that the failed attempt counts and the deleted request does not.
+    assert_eq!(kpis.attestations_approved, 1);
+    assert_eq!(kpis.attestations_failed, 0);
+    assert_eq!(kpis.tx_failure_rate, Some(0.5));
+    assert_eq!(kpis.total_claimers, 1);
+}
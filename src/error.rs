@@ -3,12 +3,16 @@ use serde_json::error::Category;
 use subxt::ext::sp_core::crypto::SecretStringError;
 use thiserror::Error;
 
+use crate::database::dto::{
+    AttestationState, AttestationTransition, ErrorResponse, InvalidTransition, MessageError,
+};
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     #[error("Blockchain error: {0}")]
-    Subxt(#[from] subxt::Error),
+    Subxt(Box<subxt::Error>),
     #[error("Server error: {0}")]
     ActixWeb(#[from] actix_web::Error),
     #[error("Signature error: {0}")]
@@ -25,19 +29,187 @@ pub enum AppError {
     Did(&'static str),
     #[error("Attestation error: {0}")]
     Attestation(&'static str),
+    #[error("Credential error: {0}")]
+    Credential(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Evidence error: {0}")]
+    Evidence(&'static str),
+    #[error("Quote error: {0}")]
+    Quote(&'static str),
+    #[error("Payment error: {0}")]
+    Payment(&'static str),
+    #[error("Message error: {0}")]
+    Message(String),
+    #[error("Delegation error: {0}")]
+    Delegation(&'static str),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(&'static str),
+    #[error("Forbidden: {0}")]
+    Forbidden(&'static str),
+    #[error("Conflict: {0}")]
+    Conflict(&'static str),
+    #[error("Bad request: {0}")]
+    BadRequest(&'static str),
+    #[error("Can not {transition} an attestation request which is {state}")]
+    InvalidTransition {
+        state: AttestationState,
+        transition: AttestationTransition,
+    },
+}
+
+fn status_code_name(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::PAYMENT_REQUIRED => "payment_required",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        status if status.is_client_error() => "client_error",
+        _ => "internal_error",
+    }
+}
+
+/// Invalid state transitions are rejected by the database with a dedicated SQLSTATE, so that they
+/// are not confused with violations of the constraints of the tables.
+fn is_invalid_transition(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "P0409")
+}
+
+impl AppError {
+    /// Machine readable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(sqlx::Error::RowNotFound) => "not_found",
+            AppError::Database(e) if is_invalid_transition(e) => "invalid_state_transition",
+            AppError::Database(_) => "database_error",
+            AppError::Subxt(_) => "chain_error",
+            AppError::ActixWeb(e) => status_code_name(e.as_response_error().status_code()),
+            AppError::Secret(_) => "signature_error",
+            AppError::Json(_) => "invalid_json",
+            AppError::Hex(_) => "invalid_hex",
+            AppError::Challenge(_) => "challenge_error",
+            AppError::LightDid(_) => "light_did_error",
+            AppError::Did(_) => "did_error",
+            AppError::Attestation(_) => "attestation_error",
+            AppError::Credential(_) => "invalid_credential",
+            AppError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => "not_found",
+            AppError::Io(_) => "io_error",
+            AppError::Evidence(_) => "evidence_error",
+            AppError::Quote(_) => "invalid_quote",
+            AppError::Payment(_) => "payment_required",
+            AppError::Message(_) => "invalid_message",
+            AppError::Delegation(_) => "delegation_error",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Conflict(_) => "conflict",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::InvalidTransition { .. } => "invalid_state_transition",
+        }
+    }
+
+    /// Message which is shown to the client. Internal errors are not exposed, since they may
+    /// contain details of the database or the chain connection.
+    fn public_message(&self, status: StatusCode) -> String {
+        match self {
+            _ if status.is_server_error() => "Internal server error".to_string(),
+            AppError::Database(sqlx::Error::RowNotFound) => "Resource not found".to_string(),
+            AppError::Database(_) => {
+                "The resource can not be changed in its current state".to_string()
+            }
+            AppError::Io(_) => "Resource not found".to_string(),
+            AppError::ActixWeb(e) => e.to_string(),
+            AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::Conflict(message)
+            | AppError::BadRequest(message) => message.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            AppError::Json(e) if e.line() > 0 => Some(serde_json::json!({
+                "line": e.line(),
+                "column": e.column(),
+            })),
+            AppError::InvalidTransition { state, transition } => Some(serde_json::json!({
+                "currentState": state,
+                "transition": transition,
+            })),
+            _ => None,
+        }
+    }
+}
+
+impl From<InvalidTransition> for AppError {
+    fn from(error: InvalidTransition) -> Self {
+        AppError::InvalidTransition {
+            state: error.state,
+            transition: error.transition,
+        }
+    }
+}
+
+impl From<MessageError> for AppError {
+    fn from(error: MessageError) -> Self {
+        match error {
+            MessageError::Content(e) => AppError::Json(e),
+            e => AppError::Message(e.to_string()),
+        }
+    }
+}
+
+impl From<subxt::Error> for AppError {
+    fn from(error: subxt::Error) -> Self {
+        AppError::Subxt(Box::new(error))
+    }
 }
 
 impl actix_web::error::ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
-        log::error!("{}", self.to_string());
-        HttpResponse::build(self.status_code()).body(self.to_string())
+        let status = self.status_code();
+        if status.is_server_error() {
+            log::error!("{}", self);
+        } else {
+            log::warn!("{}", self);
+        }
+
+        HttpResponse::build(status).json(ErrorResponse {
+            code: self.code().to_string(),
+            message: self.public_message(status),
+            details: self.details(),
+            request_id: crate::telemetry::current_request_id(),
+        })
     }
 
     fn status_code(&self) -> StatusCode {
         match self {
             AppError::Database(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
-            AppError::Hex(hex::FromHexError::InvalidHexCharacter { .. }) => StatusCode::BAD_REQUEST,
-            AppError::Hex(hex::FromHexError::InvalidStringLength) => StatusCode::BAD_REQUEST,
+            AppError::Database(e) if is_invalid_transition(e) => StatusCode::CONFLICT,
+            AppError::Hex(_) => StatusCode::BAD_REQUEST,
+            AppError::Challenge(_) => StatusCode::BAD_REQUEST,
+            AppError::LightDid(_) => StatusCode::BAD_REQUEST,
+            AppError::Did(_) => StatusCode::BAD_REQUEST,
+            AppError::Credential(_) => StatusCode::BAD_REQUEST,
+            AppError::Quote(_) => StatusCode::BAD_REQUEST,
+            AppError::Payment(_) => StatusCode::PAYMENT_REQUIRED,
+            AppError::Message(_) => StatusCode::BAD_REQUEST,
+            AppError::Delegation(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidTransition { .. } => StatusCode::CONFLICT,
+            AppError::ActixWeb(e) => e.as_response_error().status_code(),
+            AppError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND,
             AppError::Json(e) => match e.classify() {
                 Category::Data => StatusCode::BAD_REQUEST,
                 Category::Syntax => StatusCode::BAD_REQUEST,
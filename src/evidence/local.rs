@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+use crate::{error::AppError, evidence::EvidenceStorage};
+
+/// Stores evidence documents as files in a directory on the local filesystem.
+pub struct LocalFileStorage {
+    directory: PathBuf,
+}
+
+impl LocalFileStorage {
+    pub fn new(directory: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(directory)?;
+        Ok(LocalFileStorage {
+            directory: directory.to_path_buf(),
+        })
+    }
+}
+
+#[async_trait]
+impl EvidenceStorage for LocalFileStorage {
+    async fn store(&self, key: &str, content: &[u8]) -> Result<(), AppError> {
+        tokio::fs::write(self.directory.join(key), content).await?;
+        Ok(())
+    }
+
+    async fn load(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        Ok(tokio::fs::read(self.directory.join(key)).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match tokio::fs::remove_file(self.directory.join(key)).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Storage of supporting evidence documents for attestation requests.
+//! Documents are encrypted with a symmetric key before they are handed to the storage backend,
+//! so backends only ever see ciphertext.
+
+mod local;
+
+#[cfg(test)]
+mod tests;
+
+use async_trait::async_trait;
+use sodiumoxide::crypto::secretbox;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    configuration::EvidenceConfig,
+    database::querys::{get_evidences, mark_evidence_purged},
+    error::AppError,
+    AppState,
+};
+
+pub use local::LocalFileStorage;
+
+/// Backend which persists the encrypted evidence documents.
+#[async_trait]
+pub trait EvidenceStorage: Send + Sync {
+    async fn store(&self, key: &str, content: &[u8]) -> Result<(), AppError>;
+    async fn load(&self, key: &str) -> Result<Vec<u8>, AppError>;
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+#[derive(Clone)]
+pub struct EvidenceStore {
+    storage: Arc<dyn EvidenceStorage>,
+    key: secretbox::Key,
+    pub max_file_size: usize,
+}
+
+impl EvidenceStore {
+    pub fn new(
+        storage: Arc<dyn EvidenceStorage>,
+        key: secretbox::Key,
+        max_file_size: usize,
+    ) -> Self {
+        EvidenceStore {
+            storage,
+            key,
+            max_file_size,
+        }
+    }
+
+    pub fn from_config(config: &EvidenceConfig) -> anyhow::Result<Self> {
+        let storage = LocalFileStorage::new(&config.path)?;
+        Ok(EvidenceStore::new(
+            Arc::new(storage),
+            config.get_encryption_key()?,
+            config.max_file_size,
+        ))
+    }
+
+    pub async fn save(&self, evidence_id: &Uuid, content: &[u8]) -> Result<(), AppError> {
+        let nonce = secretbox::gen_nonce();
+        let mut sealed = nonce.0.to_vec();
+        sealed.extend(secretbox::seal(content, &nonce, &self.key));
+        self.storage.store(&evidence_id.to_string(), &sealed).await
+    }
+
+    pub async fn read(&self, evidence_id: &Uuid) -> Result<Vec<u8>, AppError> {
+        let sealed = self.storage.load(&evidence_id.to_string()).await?;
+        if sealed.len() < secretbox::NONCEBYTES {
+            return Err(AppError::Evidence("Stored evidence is malformed"));
+        }
+        let (nonce, cipher_text) = sealed.split_at(secretbox::NONCEBYTES);
+        let nonce =
+            secretbox::Nonce::from_slice(nonce).ok_or(AppError::Evidence("Invalid nonce"))?;
+        secretbox::open(cipher_text, &nonce, &self.key)
+            .map_err(|_| AppError::Evidence("Unable to decrypt evidence"))
+    }
+
+    pub async fn delete(&self, evidence_id: &Uuid) -> Result<(), AppError> {
+        self.storage.delete(&evidence_id.to_string()).await
+    }
+}
+
+/// Removes all evidence documents of an attestation request once a decision about it is made.
+/// Failures are logged, since the decision itself must not be affected by them.
+pub async fn purge_evidences(attestation_request_id: &Uuid, state: &AppState) {
+    let Some(evidence_store) = &state.evidence_store else {
+        return;
+    };
+    let db_executor = &state.db_executor;
+
+    let evidences = match get_evidences(attestation_request_id, db_executor).await {
+        Ok(evidences) => evidences,
+        Err(err) => {
+            log::error!("Error: Something went wrong with get_evidences: {:?}", err);
+            return;
+        }
+    };
+
+    for evidence in evidences.iter().filter(|e| e.purged_at.is_none()) {
+        if let Err(err) = evidence_store.delete(&evidence.id).await {
+            log::error!(
+                "Error: Evidence {:?} could not be deleted: {:?}",
+                evidence.id,
+                err
+            );
+            continue;
+        }
+
+        if let Err(err) = mark_evidence_purged(&evidence.id, db_executor).await {
+            log::error!(
+                "Error: Something went wrong with mark_evidence_purged: {:?}",
+                err
+            );
+        }
+    }
+
+    log::info!(
+        "Evidences of attestation with id {:?} are purged",
+        attestation_request_id
+    );
+}
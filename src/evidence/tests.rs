@@ -0,0 +1,68 @@
+use sodiumoxide::crypto::secretbox;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::evidence::{EvidenceStore, LocalFileStorage};
+
+fn get_evidence_store() -> (EvidenceStore, std::path::PathBuf) {
+    let directory = std::env::temp_dir().join(format!("evidence-{}", Uuid::new_v4()));
+    let storage = LocalFileStorage::new(&directory).expect("Creating storage should not fail");
+    let store = EvidenceStore::new(Arc::new(storage), secretbox::gen_key(), 1024);
+    (store, directory)
+}
+
+#[tokio::test]
+async fn test_evidence_store_roundtrip() {
+    // Arrange: Create a store in a temporary directory.
+    let (store, directory) = get_evidence_store();
+    let evidence_id = Uuid::new_v4();
+    let content = b"passport scan";
+
+    // Act: Save and read the evidence.
+    store
+        .save(&evidence_id, content)
+        .await
+        .expect("Saving evidence should not fail");
+    let read_content = store
+        .read(&evidence_id)
+        .await
+        .expect("Reading evidence should not fail");
+
+    // Assert: Check that the content is returned decrypted but stored encrypted.
+    assert_eq!(read_content, content);
+    let stored_content =
+        std::fs::read(directory.join(evidence_id.to_string())).expect("Evidence file should exist");
+    assert_ne!(stored_content, content);
+    assert!(!stored_content
+        .windows(content.len())
+        .any(|window| window == content));
+
+    // Act: Delete the evidence.
+    store
+        .delete(&evidence_id)
+        .await
+        .expect("Deleting evidence should not fail");
+
+    // Assert: Check that the evidence is gone.
+    assert!(store.read(&evidence_id).await.is_err());
+    std::fs::remove_dir_all(directory).expect("Removing directory should not fail");
+}
+
+#[tokio::test]
+async fn test_evidence_store_wrong_key() {
+    // Arrange: Save evidence with one key.
+    let (store, directory) = get_evidence_store();
+    let evidence_id = Uuid::new_v4();
+    store
+        .save(&evidence_id, b"utility bill")
+        .await
+        .expect("Saving evidence should not fail");
+
+    // Act: Read it with another key.
+    let storage = LocalFileStorage::new(&directory).expect("Creating storage should not fail");
+    let other_store = EvidenceStore::new(Arc::new(storage), secretbox::gen_key(), 1024);
+
+    // Assert: Check that the evidence can not be decrypted.
+    assert!(other_store.read(&evidence_id).await.is_err());
+    std::fs::remove_dir_all(directory).expect("Removing directory should not fail");
+}
@@ -0,0 +1,195 @@
+//! Self-check of the configured keys against the chain. It runs at startup and on `/health`,
+//! so that misconfigured seeds are detected before the first attestation fails.
+//! The readiness check reports the state of the dependencies for orchestrators.
+
+use serde::Serialize;
+use std::time::Duration;
+use subxt::{ext::sp_core::Pair, utils::AccountId32, OnlineClient};
+
+use crate::{
+    database::{connection::latest_migration_version, querys::get_migration_version},
+    error::AppError,
+    kilt::{
+        femto_to_kilt, get_did_doc, get_did_uri, get_encryption_key_from_fulldid_key_uri,
+        get_free_balance, get_kilt_address, has_verification_key, KiltConfig,
+    },
+    AppState,
+};
+
+#[derive(Serialize, Clone, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfCheckReport {
+    pub did: String,
+    pub payer_balance: Option<String>,
+    /// Misconfigurations which prevent attesting credentials.
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl SelfCheckReport {
+    pub fn is_healthy(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+pub async fn run_self_check(state: &AppState) -> Result<SelfCheckReport, AppError> {
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    let did = get_did_uri(&state.attester_did);
+
+    let mut report = SelfCheckReport {
+        did: did.clone(),
+        payer_balance: None,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    match get_did_doc(&did, &chain_client).await {
+        Ok(doc) => {
+            if !has_verification_key(&doc, doc.attestation_key, state.signer.signer().public()) {
+                report.errors.push(
+                    "Attestation seed does not match the assertion key of the DID".to_string(),
+                );
+            }
+        }
+        Err(e) => report
+            .errors
+            .push(format!("Attester DID could not be resolved: {}", e)),
+    }
+
+    let configured_key =
+        hex::decode(state.session.nacl_public_key.trim_start_matches("0x")).unwrap_or_default();
+    match get_encryption_key_from_fulldid_key_uri(&state.session.key_uri, &chain_client).await {
+        Ok(key) if key == state.encryption_key.public_key() && key.0[..] == configured_key[..] => {}
+        Ok(_) => report
+            .errors
+            .push("Session key URI does not resolve to the configured NaCl public key".to_string()),
+        Err(e) => report
+            .errors
+            .push(format!("Session key URI could not be resolved: {}", e)),
+    }
+
+    let payer = AccountId32(state.payer.account_id().clone().into());
+    let balance = get_free_balance(&payer, &chain_client).await?;
+    report.payer_balance = Some(femto_to_kilt(balance));
+    if let Some(min_balance) = state.min_payer_balance {
+        if balance < min_balance {
+            report.warnings.push(format!(
+                "Payer balance of {} KILT is below {} KILT",
+                femto_to_kilt(balance),
+                femto_to_kilt(min_balance)
+            ));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Time after which an unresponsive dependency is reported as unavailable.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Clone, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStatus {
+    pub available: bool,
+    pub pool_size: u32,
+    pub idle_connections: usize,
+    pub migration_version: Option<i64>,
+    pub expected_migration_version: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainStatus {
+    pub available: bool,
+    pub endpoint: String,
+    pub latest_block: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerStatus {
+    pub payer: String,
+    pub attester_did: String,
+    pub attestation_key: String,
+    pub authentication_key: String,
+    pub delegation_key: String,
+}
+
+#[derive(Serialize, Clone, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub database: DatabaseStatus,
+    pub chain: ChainStatus,
+    pub signers: SignerStatus,
+}
+
+async fn check_database(state: &AppState) -> DatabaseStatus {
+    let pool = &state.db_executor;
+    let expected_migration_version = latest_migration_version();
+
+    let result = tokio::time::timeout(READINESS_TIMEOUT, get_migration_version(pool)).await;
+    let (migration_version, error) = match result {
+        Ok(Ok(version)) if version == expected_migration_version => (version, None),
+        Ok(Ok(version)) => (version, Some("Database migrations are pending".to_string())),
+        Ok(Err(e)) => (None, Some(e.to_string())),
+        Err(_) => (None, Some("Database did not respond in time".to_string())),
+    };
+
+    DatabaseStatus {
+        available: error.is_none(),
+        pool_size: pool.size(),
+        idle_connections: pool.num_idle(),
+        migration_version,
+        expected_migration_version,
+        error,
+    }
+}
+
+async fn get_latest_block(endpoint: &str) -> Result<u64, AppError> {
+    let chain_client = OnlineClient::<KiltConfig>::from_url(endpoint).await?;
+    Ok(chain_client.blocks().at_latest().await?.number())
+}
+
+async fn check_chain(state: &AppState) -> ChainStatus {
+    let result = tokio::time::timeout(READINESS_TIMEOUT, get_latest_block(&state.endpoint)).await;
+    let (latest_block, error) = match result {
+        Ok(Ok(block)) => (Some(block), None),
+        Ok(Err(e)) => (None, Some(e.to_string())),
+        Err(_) => (None, Some("Chain did not respond in time".to_string())),
+    };
+
+    ChainStatus {
+        available: error.is_none(),
+        endpoint: state.endpoint.clone(),
+        latest_block,
+        error,
+    }
+}
+
+fn get_signers(state: &AppState) -> SignerStatus {
+    SignerStatus {
+        payer: get_kilt_address(&AccountId32(state.payer.account_id().clone().into())),
+        attester_did: get_did_uri(&state.attester_did),
+        attestation_key: get_kilt_address(&AccountId32(state.signer.account_id().clone().into())),
+        authentication_key: get_kilt_address(&AccountId32(
+            state.authentication_signer.account_id().clone().into(),
+        )),
+        delegation_key: get_kilt_address(&AccountId32(
+            state.delegation_signer.account_id().clone().into(),
+        )),
+    }
+}
+
+pub async fn check_readiness(state: &AppState) -> ReadinessReport {
+    let (database, chain) = tokio::join!(check_database(state), check_chain(state));
+
+    ReadinessReport {
+        ready: database.available && chain.available,
+        database,
+        chain,
+        signers: get_signers(state),
+    }
+}
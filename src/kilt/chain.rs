@@ -0,0 +1,199 @@
+//! Operations of the attester on the KILT chain which are used by the attestation and credential
+//! routes. The routes only depend on the [`Chain`] trait, so that they can be run against an
+//! in-memory chain in tests.
+
+use async_trait::async_trait;
+use sodiumoxide::crypto::box_;
+use std::sync::Arc;
+use subxt::{
+    ext::sp_core::{sr25519, H256},
+    tx::PairSigner,
+    utils::AccountId32,
+    OnlineClient,
+};
+
+use crate::{
+    error::AppError,
+    kilt::{self, KiltConfig, VerificationKey},
+};
+
+/// Attestation of a claim hash which is stored on chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OnChainAttestation {
+    pub ctype_hash: H256,
+    pub attester: AccountId32,
+    pub revoked: bool,
+}
+
+/// Delegation node which is stored on chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OnChainDelegationNode {
+    pub hierarchy_root_id: H256,
+    pub parent: Option<H256>,
+    pub owner: AccountId32,
+    pub permissions: u32,
+    pub revoked: bool,
+}
+
+/// Outcome of an extrinsic in a finalized block and the transfers which it made.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtrinsicTransfers {
+    pub success: bool,
+    pub transfers: Vec<(AccountId32, u128)>,
+}
+
+#[async_trait]
+pub trait Chain: Send + Sync {
+    /// Anchors the attestation of the claim hash and returns the encoded DID call.
+    async fn create_claim(
+        &self,
+        claim_hash: H256,
+        ctype_hash: H256,
+        delegation_id: Option<H256>,
+    ) -> Result<Vec<u8>, subxt::Error>;
+
+    /// Revokes the attestation of the claim hash and returns the encoded DID call.
+    async fn revoke_claim(&self, claim_hash: H256) -> Result<Vec<u8>, subxt::Error>;
+
+    /// Resolves the encryption key which is referenced by the key URI of a full DID.
+    async fn get_encryption_key(&self, key_uri: &str) -> Result<box_::PublicKey, AppError>;
+
+    /// Returns the key URI of the current authentication key of the attester DID.
+    async fn get_authentication_key_uri(&self) -> Result<String, AppError>;
+
+    /// Returns the transaction counter which the next DID call of the attester has to use.
+    async fn get_next_tx_counter(&self) -> Result<u64, AppError>;
+
+    /// Returns the attestation of the claim hash, if there is one.
+    async fn get_attestation(
+        &self,
+        claim_hash: H256,
+    ) -> Result<Option<OnChainAttestation>, AppError>;
+
+    /// Resolves the authentication key which is referenced by the key URI of a full DID.
+    async fn get_authentication_key(&self, key_uri: &str) -> Result<VerificationKey, AppError>;
+
+    /// Looks up the extrinsic `tx_hash` in the finalized block `block_hash`.
+    async fn get_extrinsic_transfers(
+        &self,
+        block_hash: H256,
+        tx_hash: H256,
+    ) -> Result<Option<ExtrinsicTransfers>, AppError>;
+
+    /// Returns the delegation node, if there is one.
+    async fn get_delegation_node(
+        &self,
+        delegation_id: H256,
+    ) -> Result<Option<OnChainDelegationNode>, AppError>;
+
+    /// Returns the CType hash for which the delegation hierarchy was created, if there is one.
+    async fn get_delegation_hierarchy_ctype_hash(
+        &self,
+        root_id: H256,
+    ) -> Result<Option<H256>, AppError>;
+}
+
+/// Chain which is accessed through a KILT node. A new connection is opened for every operation.
+pub struct KiltChain {
+    endpoint: String,
+    attester_did: AccountId32,
+    payer: Arc<PairSigner<KiltConfig, sr25519::Pair>>,
+    signer: Arc<PairSigner<KiltConfig, sr25519::Pair>>,
+}
+
+impl KiltChain {
+    pub fn new(
+        endpoint: String,
+        attester_did: AccountId32,
+        payer: Arc<PairSigner<KiltConfig, sr25519::Pair>>,
+        signer: Arc<PairSigner<KiltConfig, sr25519::Pair>>,
+    ) -> Self {
+        KiltChain {
+            endpoint,
+            attester_did,
+            payer,
+            signer,
+        }
+    }
+
+    async fn client(&self) -> Result<OnlineClient<KiltConfig>, subxt::Error> {
+        OnlineClient::<KiltConfig>::from_url(&self.endpoint).await
+    }
+}
+
+#[async_trait]
+impl Chain for KiltChain {
+    async fn create_claim(
+        &self,
+        claim_hash: H256,
+        ctype_hash: H256,
+        delegation_id: Option<H256>,
+    ) -> Result<Vec<u8>, subxt::Error> {
+        kilt::create_claim(
+            claim_hash,
+            ctype_hash,
+            delegation_id,
+            &self.attester_did,
+            &self.client().await?,
+            &self.payer,
+            &self.signer,
+        )
+        .await
+    }
+
+    async fn revoke_claim(&self, claim_hash: H256) -> Result<Vec<u8>, subxt::Error> {
+        kilt::revoke_claim(
+            claim_hash,
+            &self.attester_did,
+            &self.client().await?,
+            &self.payer,
+            &self.signer,
+        )
+        .await
+    }
+
+    async fn get_encryption_key(&self, key_uri: &str) -> Result<box_::PublicKey, AppError> {
+        kilt::get_encryption_key_from_fulldid_key_uri(key_uri, &self.client().await?).await
+    }
+
+    async fn get_authentication_key_uri(&self) -> Result<String, AppError> {
+        kilt::get_authentication_key_uri(&self.attester_did, &self.client().await?).await
+    }
+
+    async fn get_next_tx_counter(&self) -> Result<u64, AppError> {
+        Ok(kilt::utils::get_next_tx_counter(&self.client().await?, &self.attester_did).await?)
+    }
+
+    async fn get_attestation(
+        &self,
+        claim_hash: H256,
+    ) -> Result<Option<OnChainAttestation>, AppError> {
+        kilt::get_attestation(claim_hash, &self.client().await?).await
+    }
+
+    async fn get_authentication_key(&self, key_uri: &str) -> Result<VerificationKey, AppError> {
+        kilt::get_authentication_key_from_fulldid(key_uri, &self.client().await?).await
+    }
+
+    async fn get_extrinsic_transfers(
+        &self,
+        block_hash: H256,
+        tx_hash: H256,
+    ) -> Result<Option<ExtrinsicTransfers>, AppError> {
+        kilt::get_extrinsic_transfers(block_hash, tx_hash, &self.client().await?).await
+    }
+
+    async fn get_delegation_node(
+        &self,
+        delegation_id: H256,
+    ) -> Result<Option<OnChainDelegationNode>, AppError> {
+        kilt::get_delegation_node(delegation_id, &self.client().await?).await
+    }
+
+    async fn get_delegation_hierarchy_ctype_hash(
+        &self,
+        root_id: H256,
+    ) -> Result<Option<H256>, AppError> {
+        kilt::get_delegation_hierarchy_ctype_hash(root_id, &self.client().await?).await
+    }
+}
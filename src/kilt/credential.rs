@@ -0,0 +1,110 @@
+//! Hashing of KILT credentials.
+//! A credential commits to its claim contents by hashing every statement of the claim,
+//! salting the statement hashes with the nonces of the `claimNonceMap` and hashing the
+//! salted hashes together with the legitimations and the delegation id into the root hash.
+
+use blake2::{Blake2b, Digest};
+use hmac::digest::typenum::U32;
+
+use crate::{database::dto::Credential, error::AppError};
+
+type Blake2b256 = Blake2b<U32>;
+
+fn hash_str(value: &str) -> String {
+    let mut hasher = Blake2b256::new();
+    hasher.update(value);
+    format!("0x{}", hex::encode(hasher.finalize()))
+}
+
+fn decode_hash(hash: &str) -> Result<Vec<u8>, AppError> {
+    let bytes = hex::decode(hash.trim_start_matches("0x"))
+        .map_err(|_| AppError::Credential("Hash is not valid hex".to_string()))?;
+    if bytes.len() != 32 {
+        return Err(AppError::Credential(
+            "Hash is expected to have 32 bytes".to_string(),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Builds the normalized statements of the claim, one per property plus the owner.
+fn make_statements(credential: &Credential) -> Result<Vec<String>, AppError> {
+    let claim = &credential.claim;
+    let vocabulary = format!("kilt:ctype:{}#", claim.ctype_hash);
+
+    let mut statements = vec![serde_json::to_string(
+        &serde_json::json!({ "@id": claim.owner }),
+    )?];
+
+    let contents = claim.contents.as_object().ok_or(AppError::Credential(
+        "Claim contents are malformed".to_string(),
+    ))?;
+
+    for (key, value) in contents {
+        let mut statement = serde_json::Map::new();
+        statement.insert(format!("{}{}", vocabulary, key), value.clone());
+        statements.push(serde_json::to_string(&statement)?);
+    }
+
+    Ok(statements)
+}
+
+/// Calculates the root hash of the credential from its claim hashes, legitimations and delegation id.
+pub fn calculate_root_hash(credential: &Credential) -> Result<String, AppError> {
+    let mut hasher = Blake2b256::new();
+
+    for claim_hash in credential.claim_hashes.iter() {
+        hasher.update(decode_hash(claim_hash)?);
+    }
+
+    for legitimation in credential.legitimations.iter().flatten() {
+        hasher.update(decode_hash(&legitimation.root_hash)?);
+    }
+
+    if let Some(delegation_id) = &credential.delegation_id {
+        hasher.update(decode_hash(delegation_id)?);
+    }
+
+    Ok(format!("0x{}", hex::encode(hasher.finalize())))
+}
+
+/// Checks that the claim hashes commit to the claim contents and that the root hash matches.
+pub fn verify_credential_hashes(credential: &Credential) -> Result<(), AppError> {
+    let mut salted_hashes = make_statements(credential)?
+        .iter()
+        .map(|statement| {
+            let digest = hash_str(statement);
+            let nonce = credential
+                .claim_nonce_map
+                .get(&digest)
+                .ok_or(AppError::Credential(
+                    "Nonce for a claim statement is missing".to_string(),
+                ))?;
+            Ok(hash_str(&format!("{}{}", nonce, digest)))
+        })
+        .collect::<Result<Vec<String>, AppError>>()?;
+
+    let mut claim_hashes = credential
+        .claim_hashes
+        .iter()
+        .map(|hash| hash.to_lowercase())
+        .collect::<Vec<String>>();
+
+    salted_hashes.sort();
+    claim_hashes.sort();
+
+    if salted_hashes != claim_hashes {
+        return Err(AppError::Credential(
+            "Claim hashes do not match the claim contents".to_string(),
+        ));
+    }
+
+    let root_hash = calculate_root_hash(credential)?;
+    if root_hash != credential.root_hash.to_lowercase() {
+        return Err(AppError::Credential(
+            "Root hash does not match the credential".to_string(),
+        ));
+    }
+
+    Ok(())
+}
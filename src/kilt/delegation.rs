@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+
+use subxt::{config::Hasher, ext::sp_core::H256, utils::AccountId32, Config, OnlineClient};
+
+use crate::{
+    database::dto::{DelegationHierarchy, DelegationNode, DelegationPermissions},
+    error::AppError,
+    kilt::{did::get_did_uri, runtime, Chain, KiltConfig, OnChainDelegationNode},
+};
+
+pub fn parse_delegation_id(delegation_id: &str) -> Result<H256, AppError> {
+    let bytes = hex::decode(delegation_id.trim_start_matches("0x"))?;
+    if bytes.len() != 32 {
+        return Err(AppError::Delegation("Delegation ID has a wrong format"));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+/// Returns the delegation ID under which a credential is attested. The credential has to commit
+/// to the delegation configured for its CType, since the delegation ID is part of the root hash.
+pub fn get_delegation_id(
+    configured_delegation_id: Option<&str>,
+    credential_delegation_id: Option<&str>,
+) -> Result<Option<H256>, AppError> {
+    match (configured_delegation_id, credential_delegation_id) {
+        (None, None) => Ok(None),
+        (Some(configured), Some(requested)) if configured.eq_ignore_ascii_case(requested) => {
+            parse_delegation_id(configured).map(Some)
+        }
+        _ => Err(AppError::Delegation(
+            "Credential does not use the delegation of the CType",
+        )),
+    }
+}
+
+/// Returns the delegation node from the storage of the chain.
+pub async fn get_delegation_node(
+    delegation_id: H256,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<Option<OnChainDelegationNode>, AppError> {
+    let node = chain_client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(
+            &runtime::storage()
+                .delegation()
+                .delegation_nodes(delegation_id),
+        )
+        .await?;
+
+    Ok(node.map(|node| OnChainDelegationNode {
+        hierarchy_root_id: node.hierarchy_root_id,
+        parent: node.parent,
+        owner: node.details.owner,
+        permissions: node.details.permissions.bits,
+        revoked: node.details.revoked,
+    }))
+}
+
+/// Returns the CType hash of the delegation hierarchy from the storage of the chain.
+pub async fn get_delegation_hierarchy_ctype_hash(
+    root_id: H256,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<Option<H256>, AppError> {
+    let hierarchy = chain_client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(
+            &runtime::storage()
+                .delegation()
+                .delegation_hierarchies(root_id),
+        )
+        .await?;
+
+    Ok(hierarchy.map(|hierarchy| hierarchy.ctype_hash))
+}
+
+/// Checks that the attester owns the delegation node, that the node may attest and that neither
+/// the node nor one of its parents is revoked. The hierarchy has to be created for the CType.
+pub async fn verify_delegation_node(
+    delegation_id: H256,
+    attester: &AccountId32,
+    ctype_hash: H256,
+    chain: &dyn Chain,
+) -> Result<(), AppError> {
+    let node = chain
+        .get_delegation_node(delegation_id)
+        .await?
+        .ok_or(AppError::Delegation("Delegation node not found"))?;
+
+    if &node.owner != attester {
+        return Err(AppError::Delegation(
+            "Delegation node is not owned by the attester",
+        ));
+    }
+
+    if !DelegationPermissions::from(node.permissions).attest {
+        return Err(AppError::Delegation(
+            "Delegation node is not allowed to attest",
+        ));
+    }
+
+    let hierarchy_ctype_hash = chain
+        .get_delegation_hierarchy_ctype_hash(node.hierarchy_root_id)
+        .await?
+        .ok_or(AppError::Delegation("Delegation hierarchy not found"))?;
+
+    if hierarchy_ctype_hash != ctype_hash {
+        return Err(AppError::Delegation(
+            "Delegation hierarchy was created for another CType",
+        ));
+    }
+
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if node.revoked {
+            return Err(AppError::Delegation("Delegation node is revoked"));
+        }
+        current = match node.parent {
+            Some(parent) => chain.get_delegation_node(parent).await?,
+            None => None,
+        };
+    }
+
+    Ok(())
+}
+
+/// Returns the hash which the delegate signs to accept a new delegation node. It is calculated
+/// the same way as by the delegation pallet.
+pub fn get_delegation_creation_hash(
+    delegation_id: H256,
+    hierarchy_root_id: H256,
+    parent_id: H256,
+    permissions: u32,
+) -> H256 {
+    let mut hashed_values = delegation_id.as_bytes().to_vec();
+    hashed_values.extend_from_slice(hierarchy_root_id.as_bytes());
+    hashed_values.extend_from_slice(parent_id.as_bytes());
+    hashed_values.extend_from_slice(&permissions.to_le_bytes());
+    <KiltConfig as Config>::Hasher::hash(&hashed_values)
+}
+
+/// Returns the ID of the hierarchy root of a delegation node.
+pub async fn get_hierarchy_root_id(
+    delegation_id: H256,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<H256, AppError> {
+    let node = chain_client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(
+            &runtime::storage()
+                .delegation()
+                .delegation_nodes(delegation_id),
+        )
+        .await?
+        .ok_or(AppError::Delegation("Delegation node not found"))?;
+
+    Ok(node.hierarchy_root_id)
+}
+
+/// Reads a delegation hierarchy with all of its nodes from the chain.
+pub async fn get_delegation_hierarchy(
+    root_id: H256,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<DelegationHierarchy, AppError> {
+    let storage = chain_client.storage().at_latest().await?;
+
+    let hierarchy = storage
+        .fetch(
+            &runtime::storage()
+                .delegation()
+                .delegation_hierarchies(root_id),
+        )
+        .await?
+        .ok_or(AppError::Delegation("Delegation hierarchy not found"))?;
+
+    let mut nodes = Vec::new();
+    let mut pending = VecDeque::from([root_id]);
+    while let Some(id) = pending.pop_front() {
+        let node = storage
+            .fetch(&runtime::storage().delegation().delegation_nodes(id))
+            .await?
+            .ok_or(AppError::Delegation("Delegation node not found"))?;
+
+        let children: Vec<H256> = node.children.0.into_iter().collect();
+        pending.extend(children.iter().copied());
+
+        nodes.push(DelegationNode {
+            id: format!("{:?}", id),
+            parent_id: node.parent.map(|parent| format!("{:?}", parent)),
+            children: children
+                .iter()
+                .map(|child| format!("{:?}", child))
+                .collect(),
+            owner: get_did_uri(&node.details.owner),
+            permissions: node.details.permissions.bits.into(),
+            revoked: node.details.revoked,
+        });
+    }
+
+    Ok(DelegationHierarchy {
+        root_id: format!("{:?}", root_id),
+        ctype_hash: format!("{:?}", hierarchy.ctype_hash),
+        nodes,
+    })
+}
@@ -2,21 +2,51 @@ use base58::FromBase58;
 use serde_with::{serde_as, Bytes};
 use sodiumoxide::crypto::box_;
 use std::str::FromStr;
-use subxt::{ext::sp_core, OnlineClient};
+use subxt::{
+    ext::sp_core::{
+        self,
+        crypto::{Ss58AddressFormat, Ss58Codec},
+        ed25519, sr25519, Pair,
+    },
+    utils::AccountId32,
+    OnlineClient,
+};
 
 use crate::{
     error::AppError,
-    kilt::{runtime, KiltConfig},
+    kilt::{runtime, Chain, KiltConfig},
+};
+
+use runtime::runtime_types;
+use runtime::runtime_types::did::{
+    did_details::DidVerificationKey,
+    did_details::{DidDetails, DidEncryptionKey, DidPublicKey, DidSignature},
 };
 
-use runtime::runtime_types::did::did_details::{DidDetails, DidEncryptionKey, DidPublicKey};
+/// SS58 prefix of KILT addresses.
+const KILT_SS58_PREFIX: u16 = 38;
+
+/// Returns the SS58 encoded KILT address of the account.
+pub fn get_kilt_address(account: &AccountId32) -> String {
+    sp_core::crypto::AccountId32::from(account.0)
+        .to_ss58check_with_version(Ss58AddressFormat::custom(KILT_SS58_PREFIX))
+}
+
+pub fn get_did_uri(did_address: &AccountId32) -> String {
+    format!("did:kilt:{}", get_kilt_address(did_address))
+}
+
+/// Returns the address of a full DID.
+pub fn parse_did(did: &str) -> Result<AccountId32, AppError> {
+    AccountId32::from_str(did.trim_start_matches("did:kilt:"))
+        .map_err(|_| AppError::Did("Invalid DID"))
+}
 
 pub async fn get_did_doc(
     did: &str,
     cli: &OnlineClient<KiltConfig>,
 ) -> Result<DidDetails, AppError> {
-    let did = subxt::utils::AccountId32::from_str(did.trim_start_matches("did:kilt:"))
-        .map_err(|_| AppError::Did("Invalid DID"))?;
+    let did = parse_did(did)?;
     let did_doc_key = runtime::storage().did().did(&did);
     let details = cli
         .storage()
@@ -62,8 +92,11 @@ pub fn parse_encryption_key_from_lightdid(did: &str) -> Result<box_::PublicKey,
     let bs: Vec<u8> = FromBase58::from_base58(chars.as_str())
         .map_err(|_| AppError::LightDid("malformed base58".to_string()))?;
 
+    let encoded_details = bs
+        .get(1..)
+        .ok_or(AppError::LightDid("malformed".to_string()))?;
     let details: LightDidDetails =
-        serde_cbor::from_slice(&bs[1..]).map_err(|e| AppError::LightDid(e.to_string()))?;
+        serde_cbor::from_slice(encoded_details).map_err(|e| AppError::LightDid(e.to_string()))?;
     box_::PublicKey::from_slice(&details.e.public_key)
         .ok_or(AppError::LightDid("Not a valid public key".to_string()))
 }
@@ -74,14 +107,19 @@ fn parse_key_uri(key_uri: &str) -> Result<(&str, sp_core::H256), AppError> {
         return Err(AppError::Did("Invalid sender key URI"));
     }
     let did = key_uri_parts[0];
-    let key_id = key_uri_parts[1];
+    let kid = parse_key_id(key_uri_parts[1])?;
+
+    Ok((did, kid))
+}
+
+/// Parses a key ID, which is either given on its own or as the fragment of a key URI.
+pub fn parse_key_id(key_id: &str) -> Result<sp_core::H256, AppError> {
+    let key_id = key_id.rsplit('#').next().unwrap_or(key_id);
     let kid_bs: [u8; 32] = hex::decode(key_id.trim_start_matches("0x"))
         .map_err(|_| AppError::Did("key ID isn't valid hex"))?
         .try_into()
         .map_err(|_| AppError::Did("key ID is expected to have 32 bytes"))?;
-    let kid = sp_core::H256::from(kid_bs);
-
-    Ok((did, kid))
+    Ok(sp_core::H256::from(kid_bs))
 }
 
 pub async fn get_encryption_key_from_fulldid_key_uri(
@@ -104,3 +142,163 @@ pub async fn get_encryption_key_from_fulldid_key_uri(
     };
     box_::PublicKey::from_slice(&pk).ok_or(AppError::Did("Invalid sender public key"))
 }
+
+/// Returns the key URI of the current authentication key of a full DID.
+pub async fn get_authentication_key_uri(
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<String, AppError> {
+    let did = get_did_uri(did_address);
+    let doc = get_did_doc(&did, chain_client).await?;
+    Ok(format!("{}#{:?}", did, doc.authentication_key))
+}
+
+/// Public key of a DID authentication key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerificationKey {
+    Sr25519([u8; 32]),
+    Ed25519([u8; 32]),
+}
+
+/// Strips the optional details of a light DID, which do not change its identity.
+fn normalize_did(did: &str) -> String {
+    if did.starts_with("did:kilt:light:") {
+        did.split(':').take(4).collect::<Vec<&str>>().join(":")
+    } else {
+        did.to_string()
+    }
+}
+
+fn get_authentication_key_from_lightdid(did: &str) -> Result<VerificationKey, AppError> {
+    // example did:kilt:light:00${authAddress}:${details}
+    let auth = did
+        .split(':')
+        .nth(3)
+        .ok_or(AppError::LightDid("malformed".to_string()))?;
+    if auth.len() < 2 || !auth.is_char_boundary(2) {
+        return Err(AppError::LightDid("malformed".to_string()));
+    }
+    let (key_type, address) = auth.split_at(2);
+    let public_key = sp_core::crypto::AccountId32::from_ss58check(address)
+        .map_err(|_| AppError::LightDid("malformed address".to_string()))?;
+    let public_key: [u8; 32] = public_key.into();
+
+    match key_type {
+        "00" => Ok(VerificationKey::Sr25519(public_key)),
+        "01" => Ok(VerificationKey::Ed25519(public_key)),
+        _ => Err(AppError::LightDid(
+            "Unsupported authentication key".to_string(),
+        )),
+    }
+}
+
+/// Returns the public key of the authentication key which is referenced by the key URI.
+pub async fn get_authentication_key_from_fulldid(
+    key_uri: &str,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<VerificationKey, AppError> {
+    let (did, kid) = parse_key_uri(key_uri)?;
+    let doc = get_did_doc(did, chain_client).await?;
+    if doc.authentication_key != kid {
+        return Err(AppError::Did(
+            "Signature was not created by an authentication key",
+        ));
+    }
+
+    let (_, details) = doc
+        .public_keys
+        .0
+        .iter()
+        .find(|&(k, _v)| *k == kid)
+        .ok_or(AppError::Did("Could not get signer public key"))?;
+
+    match &details.key {
+        DidPublicKey::PublicVerificationKey(DidVerificationKey::Sr25519(pk)) => {
+            Ok(VerificationKey::Sr25519(pk.0))
+        }
+        DidPublicKey::PublicVerificationKey(DidVerificationKey::Ed25519(pk)) => {
+            Ok(VerificationKey::Ed25519(pk.0))
+        }
+        _ => Err(AppError::Did("Unsupported signer public key")),
+    }
+}
+
+/// Verifies that `signature` was created over `data` by the authentication key of `expected_did`.
+/// Light DIDs are verified with the authentication key encoded in the DID itself.
+pub async fn verify_did_signature(
+    data: &[u8],
+    signature: &str,
+    key_uri: &str,
+    expected_did: &str,
+    chain: &dyn Chain,
+) -> Result<(), AppError> {
+    let (did, fragment) = key_uri
+        .split_once('#')
+        .ok_or(AppError::Did("Invalid signer key URI"))?;
+    if normalize_did(did) != normalize_did(expected_did) {
+        return Err(AppError::Did(
+            "Signature was not created by the expected DID",
+        ));
+    }
+
+    let public_key = if did.starts_with("did:kilt:light:") {
+        if fragment != "authentication" {
+            return Err(AppError::Did(
+                "Signature was not created by an authentication key",
+            ));
+        }
+        get_authentication_key_from_lightdid(did)?
+    } else {
+        chain.get_authentication_key(key_uri).await?
+    };
+
+    verify_signature(data, signature, &public_key).map(|_| ())
+}
+
+/// Verifies the hex encoded signature with the public key and returns it as runtime signature.
+fn verify_signature(
+    data: &[u8],
+    signature: &str,
+    public_key: &VerificationKey,
+) -> Result<DidSignature, AppError> {
+    let signature = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|_| AppError::Did("Signature isn't valid hex"))?;
+
+    let (is_valid, did_signature) = match public_key {
+        VerificationKey::Sr25519(pk) => {
+            let signature = sr25519::Signature::from_slice(&signature)
+                .ok_or(AppError::Did("Invalid signature"))?;
+            (
+                sr25519::Pair::verify(&signature, data, &sr25519::Public(*pk)),
+                DidSignature::Sr25519(runtime_types::sp_core::sr25519::Signature(signature.0)),
+            )
+        }
+        VerificationKey::Ed25519(pk) => {
+            let signature = ed25519::Signature::from_slice(&signature)
+                .ok_or(AppError::Did("Invalid signature"))?;
+            (
+                ed25519::Pair::verify(&signature, data, &ed25519::Public(*pk)),
+                DidSignature::Ed25519(runtime_types::sp_core::ed25519::Signature(signature.0)),
+            )
+        }
+    };
+
+    if !is_valid {
+        return Err(AppError::Did("Invalid signature"));
+    }
+
+    Ok(did_signature)
+}
+
+/// Verifies a signature of the current authentication key of a full DID over `data`, so that it
+/// can be submitted to the chain on behalf of this DID.
+pub async fn get_authentication_signature(
+    data: &[u8],
+    signature: &str,
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<DidSignature, AppError> {
+    let key_uri = get_authentication_key_uri(did_address, chain_client).await?;
+    let public_key = get_authentication_key_from_fulldid(&key_uri, chain_client).await?;
+    verify_signature(data, signature, &public_key)
+}
@@ -0,0 +1,177 @@
+//! Updates of the keys and service endpoints of the attester DID. The new key material is taken
+//! from the configuration, so that the configuration and the DID on chain stay in sync.
+
+use sodiumoxide::crypto::box_;
+use subxt::{
+    ext::sp_core::{sr25519, H256},
+    utils::AccountId32,
+    OnlineClient,
+};
+
+use crate::{
+    database::dto::DidDocument,
+    error::AppError,
+    kilt::{
+        did::{get_did_doc, get_did_uri},
+        runtime, KiltConfig,
+    },
+};
+
+use runtime::runtime_types;
+use runtime::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+use runtime::runtime_types::did::{
+    did_details::{DidDetails, DidEncryptionKey, DidPublicKey, DidVerificationKey},
+    pallet::Call as DidCall,
+    service_endpoints::DidEndpoint,
+};
+
+/// Keys which the attester DID is expected to have according to the configuration.
+pub struct DidKeys {
+    pub authentication_key: sr25519::Public,
+    pub attestation_key: sr25519::Public,
+    pub delegation_key: sr25519::Public,
+    pub key_agreement_key: box_::PublicKey,
+}
+
+pub enum DidUpdate {
+    SetAttestationKey(sr25519::Public),
+    SetDelegationKey(sr25519::Public),
+    AddKeyAgreementKey(box_::PublicKey),
+    RemoveKeyAgreementKey(H256),
+    AddServiceEndpoint {
+        id: String,
+        service_types: Vec<String>,
+        urls: Vec<String>,
+    },
+    RemoveServiceEndpoint(String),
+}
+
+fn to_bounded(value: String) -> BoundedVec<u8> {
+    BoundedVec(value.into_bytes())
+}
+
+/// Service IDs are stored without the `#` prefix of the service URI fragment.
+fn normalize_service_id(id: String) -> String {
+    id.trim_start_matches('#').to_string()
+}
+
+fn sr25519_key(public: sr25519::Public) -> DidVerificationKey<AccountId32> {
+    DidVerificationKey::Sr25519(runtime_types::sp_core::sr25519::Public(public.0))
+}
+
+impl From<DidUpdate> for DidCall {
+    fn from(update: DidUpdate) -> Self {
+        match update {
+            DidUpdate::SetAttestationKey(public) => DidCall::set_attestation_key {
+                new_key: sr25519_key(public),
+            },
+            DidUpdate::SetDelegationKey(public) => DidCall::set_delegation_key {
+                new_key: sr25519_key(public),
+            },
+            DidUpdate::AddKeyAgreementKey(public) => DidCall::add_key_agreement_key {
+                new_key: DidEncryptionKey::X25519(public.0),
+            },
+            DidUpdate::RemoveKeyAgreementKey(key_id) => {
+                DidCall::remove_key_agreement_key { key_id }
+            }
+            DidUpdate::AddServiceEndpoint {
+                id,
+                service_types,
+                urls,
+            } => DidCall::add_service_endpoint {
+                service_endpoint: DidEndpoint {
+                    id: to_bounded(normalize_service_id(id)),
+                    service_types: BoundedVec(service_types.into_iter().map(to_bounded).collect()),
+                    urls: BoundedVec(urls.into_iter().map(to_bounded).collect()),
+                },
+            },
+            DidUpdate::RemoveServiceEndpoint(id) => DidCall::remove_service_endpoint {
+                service_id: to_bounded(normalize_service_id(id)),
+            },
+        }
+    }
+}
+
+pub async fn did_exists(
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<bool, AppError> {
+    let did_doc_key = runtime::storage().did().did(did_address);
+    let details = chain_client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&did_doc_key)
+        .await?;
+    Ok(details.is_some())
+}
+
+/// Reads the key URIs of a full DID from the chain.
+pub async fn get_did_document(
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<DidDocument, AppError> {
+    let uri = get_did_uri(did_address);
+    let doc = get_did_doc(&uri, chain_client).await?;
+    let key_uri = |key_id: H256| format!("{}#{:?}", uri, key_id);
+
+    Ok(DidDocument {
+        authentication_key: key_uri(doc.authentication_key),
+        attestation_key: doc.attestation_key.map(key_uri),
+        delegation_key: doc.delegation_key.map(key_uri),
+        key_agreement_keys: doc.key_agreement_keys.0.into_iter().map(key_uri).collect(),
+        uri,
+    })
+}
+
+/// Checks that the key of the DID with the given ID is the sr25519 public key.
+pub fn has_verification_key(
+    doc: &DidDetails,
+    key_id: Option<H256>,
+    public: sr25519::Public,
+) -> bool {
+    key_id
+        .and_then(|key_id| doc.public_keys.0.iter().find(|(id, _)| *id == key_id))
+        .is_some_and(|(_, details)| {
+            matches!(
+                &details.key,
+                DidPublicKey::PublicVerificationKey(DidVerificationKey::Sr25519(pk)) if pk.0 == public.0
+            )
+        })
+}
+
+/// Checks that the keys of the DID on chain match the keys of the configuration.
+pub async fn verify_did_keys(
+    keys: &DidKeys,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<(), AppError> {
+    let did_address = AccountId32(keys.authentication_key.0);
+    let doc = get_did_doc(&get_did_uri(&did_address), chain_client).await?;
+
+    if !has_verification_key(&doc, Some(doc.authentication_key), keys.authentication_key) {
+        return Err(AppError::Did("Authentication key does not match"));
+    }
+
+    if !has_verification_key(&doc, doc.attestation_key, keys.attestation_key) {
+        return Err(AppError::Did("Attestation key does not match"));
+    }
+
+    if !has_verification_key(&doc, doc.delegation_key, keys.delegation_key) {
+        return Err(AppError::Did("Delegation key does not match"));
+    }
+
+    let has_key_agreement_key = doc.key_agreement_keys.0.iter().any(|key_id| {
+        doc.public_keys.0.iter().any(|(id, details)| {
+            id == key_id
+                && matches!(
+                    &details.key,
+                    DidPublicKey::PublicEncryptionKey(DidEncryptionKey::X25519(pk)) if *pk == keys.key_agreement_key.0
+                )
+        })
+    });
+    if !has_key_agreement_key {
+        return Err(AppError::Did("Key agreement key is missing"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! In-memory [`Chain`] for tests. Claims are stored in a map and every successful DID call
+//! increments the transaction counter, like it would on chain. The mock only stores the data of the
+//! chain, the checks on top of it are the same as for a real chain.
+
+use async_trait::async_trait;
+use sodiumoxide::crypto::box_;
+use std::{collections::HashMap, sync::Mutex};
+use subxt::{ext::sp_core::H256, utils::AccountId32};
+
+use crate::{
+    error::AppError,
+    kilt::{
+        get_did_uri, Chain, ExtrinsicTransfers, OnChainAttestation, OnChainDelegationNode,
+        VerificationKey,
+    },
+};
+
+/// Attestation which is anchored on the in-memory chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockClaim {
+    pub ctype_hash: H256,
+    pub delegation_id: Option<H256>,
+    pub revoked: bool,
+}
+
+#[derive(Default)]
+struct MockState {
+    claims: HashMap<H256, MockClaim>,
+    tx_counter: u64,
+    encryption_keys: HashMap<String, box_::PublicKey>,
+    extrinsics: HashMap<(H256, H256), ExtrinsicTransfers>,
+    delegation_nodes: HashMap<H256, OnChainDelegationNode>,
+    delegation_hierarchies: HashMap<H256, H256>,
+    failing: bool,
+}
+
+pub struct MockChain {
+    attester_did: AccountId32,
+    authentication_key_uri: String,
+    state: Mutex<MockState>,
+}
+
+fn parse_hash(hash: &str) -> Result<H256, AppError> {
+    let bytes = hex::decode(hash.trim_start_matches("0x"))?;
+    if bytes.len() != 32 {
+        return Err(AppError::BadRequest("Hash is expected to have 32 bytes"));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+fn chain_error(message: &str) -> subxt::Error {
+    subxt::Error::Other(message.to_string())
+}
+
+impl MockChain {
+    pub fn new(attester_did: &AccountId32) -> Self {
+        MockChain {
+            attester_did: attester_did.clone(),
+            authentication_key_uri: format!("{}#0x{}", get_did_uri(attester_did), "00".repeat(32)),
+            state: Mutex::new(MockState {
+                tx_counter: 1,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Registers the encryption key of a full DID key URI.
+    pub fn add_encryption_key(&self, key_uri: &str, key: box_::PublicKey) {
+        let mut state = self.state.lock().unwrap();
+        state.encryption_keys.insert(key_uri.to_string(), key);
+    }
+
+    /// Adds an extrinsic to the finalized block `block_hash`.
+    pub fn add_extrinsic(&self, block_hash: H256, tx_hash: H256, extrinsic: ExtrinsicTransfers) {
+        let mut state = self.state.lock().unwrap();
+        state.extrinsics.insert((block_hash, tx_hash), extrinsic);
+    }
+
+    /// Adds a delegation node to its hierarchy.
+    pub fn add_delegation_node(&self, delegation_id: H256, node: OnChainDelegationNode) {
+        let mut state = self.state.lock().unwrap();
+        state.delegation_nodes.insert(delegation_id, node);
+    }
+
+    /// Adds a delegation hierarchy for the CType, without any nodes.
+    pub fn add_delegation_hierarchy(&self, root_id: H256, ctype_hash: H256) {
+        let mut state = self.state.lock().unwrap();
+        state.delegation_hierarchies.insert(root_id, ctype_hash);
+    }
+
+    /// Lets all following transactions fail.
+    pub fn set_failing(&self, failing: bool) {
+        self.state.lock().unwrap().failing = failing;
+    }
+
+    pub fn get_claim(&self, claim_hash: &str) -> Option<MockClaim> {
+        let claim_hash = parse_hash(claim_hash).ok()?;
+        self.state.lock().unwrap().claims.get(&claim_hash).cloned()
+    }
+
+    fn submit(
+        &self,
+        apply: impl FnOnce(&mut MockState) -> Result<(), &'static str>,
+    ) -> Result<Vec<u8>, &'static str> {
+        let mut state = self.state.lock().unwrap();
+        if state.failing {
+            return Err("Transaction failed");
+        }
+        apply(&mut state)?;
+        let encoded_call = state.tx_counter.to_le_bytes().to_vec();
+        state.tx_counter += 1;
+        Ok(encoded_call)
+    }
+}
+
+#[async_trait]
+impl Chain for MockChain {
+    async fn create_claim(
+        &self,
+        claim_hash: H256,
+        ctype_hash: H256,
+        delegation_id: Option<H256>,
+    ) -> Result<Vec<u8>, subxt::Error> {
+        self.submit(|state| {
+            if state.claims.contains_key(&claim_hash) {
+                return Err("Attestation already exists");
+            }
+            state.claims.insert(
+                claim_hash,
+                MockClaim {
+                    ctype_hash,
+                    delegation_id,
+                    revoked: false,
+                },
+            );
+            Ok(())
+        })
+        .map_err(chain_error)
+    }
+
+    async fn revoke_claim(&self, claim_hash: H256) -> Result<Vec<u8>, subxt::Error> {
+        self.submit(|state| match state.claims.get_mut(&claim_hash) {
+            Some(claim) if !claim.revoked => {
+                claim.revoked = true;
+                Ok(())
+            }
+            Some(_) => Err("Attestation is already revoked"),
+            None => Err("Attestation not found"),
+        })
+        .map_err(chain_error)
+    }
+
+    async fn get_encryption_key(&self, key_uri: &str) -> Result<box_::PublicKey, AppError> {
+        let state = self.state.lock().unwrap();
+        state
+            .encryption_keys
+            .get(key_uri)
+            .copied()
+            .ok_or(AppError::Did("Could not get sender public key"))
+    }
+
+    async fn get_authentication_key_uri(&self) -> Result<String, AppError> {
+        Ok(self.authentication_key_uri.clone())
+    }
+
+    async fn get_next_tx_counter(&self) -> Result<u64, AppError> {
+        Ok(self.state.lock().unwrap().tx_counter)
+    }
+
+    /// All claims of the in-memory chain are attested by the attester DID.
+    async fn get_attestation(
+        &self,
+        claim_hash: H256,
+    ) -> Result<Option<OnChainAttestation>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .claims
+            .get(&claim_hash)
+            .map(|claim| OnChainAttestation {
+                ctype_hash: claim.ctype_hash,
+                attester: self.attester_did.clone(),
+                revoked: claim.revoked,
+            }))
+    }
+
+    /// The in-memory chain has no full DIDs of claimers.
+    async fn get_authentication_key(&self, _key_uri: &str) -> Result<VerificationKey, AppError> {
+        Err(AppError::Did("DID not found"))
+    }
+
+    async fn get_extrinsic_transfers(
+        &self,
+        block_hash: H256,
+        tx_hash: H256,
+    ) -> Result<Option<ExtrinsicTransfers>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.extrinsics.get(&(block_hash, tx_hash)).cloned())
+    }
+
+    async fn get_delegation_node(
+        &self,
+        delegation_id: H256,
+    ) -> Result<Option<OnChainDelegationNode>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.delegation_nodes.get(&delegation_id).cloned())
+    }
+
+    async fn get_delegation_hierarchy_ctype_hash(
+        &self,
+        root_id: H256,
+    ) -> Result<Option<H256>, AppError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.delegation_hierarchies.get(&root_id).copied())
+    }
+}
@@ -1,8 +1,20 @@
+mod chain;
+mod credential;
+mod delegation;
 mod did;
+mod did_management;
+#[cfg(test)]
+mod mock;
+mod payment;
+mod presentation;
+mod quote;
 mod tx;
 mod utils;
 mod well_known_did_configuration;
 
+#[cfg(test)]
+mod tests;
+
 use subxt::{
     config::polkadot::PolkadotExtrinsicParams,
     config::Config,
@@ -12,7 +24,26 @@ use subxt::{
     },
 };
 
-pub use did::{get_encryption_key_from_fulldid_key_uri, parse_encryption_key_from_lightdid};
+pub use chain::{Chain, ExtrinsicTransfers, KiltChain, OnChainAttestation, OnChainDelegationNode};
+pub use credential::verify_credential_hashes;
+pub use delegation::{
+    get_delegation_creation_hash, get_delegation_hierarchy, get_delegation_hierarchy_ctype_hash,
+    get_delegation_id, get_delegation_node, get_hierarchy_root_id, parse_delegation_id,
+    verify_delegation_node,
+};
+pub use did::{
+    get_authentication_key_from_fulldid, get_authentication_key_uri, get_authentication_signature,
+    get_did_doc, get_did_uri, get_encryption_key_from_fulldid_key_uri, get_kilt_address, parse_did,
+    parse_encryption_key_from_lightdid, parse_key_id, VerificationKey,
+};
+pub use did_management::{
+    did_exists, get_did_document, has_verification_key, verify_did_keys, DidKeys, DidUpdate,
+};
+#[cfg(test)]
+pub use mock::MockChain;
+pub use payment::{get_extrinsic_transfers, get_free_balance, verify_payment};
+pub use presentation::{get_attestation, verify_legitimations, verify_presentation};
+pub use quote::*;
 pub use tx::*;
 pub use well_known_did_configuration::*;
 
@@ -0,0 +1,92 @@
+use subxt::{config::Hasher, ext::sp_core::H256, utils::AccountId32, Config, OnlineClient};
+
+use crate::{
+    error::AppError,
+    kilt::{runtime, Chain, ExtrinsicTransfers, KiltConfig},
+};
+
+/// Looks up the extrinsic `tx_hash` in the finalized block `block_hash` and returns whether it
+/// succeeded and the transfers which it made.
+pub async fn get_extrinsic_transfers(
+    block_hash: H256,
+    tx_hash: H256,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<Option<ExtrinsicTransfers>, AppError> {
+    let block = chain_client.blocks().at(block_hash).await?;
+
+    let finalized_hash = chain_client.rpc().finalized_head().await?;
+    let finalized_number = chain_client.blocks().at(finalized_hash).await?.number();
+    let canonical_hash = chain_client
+        .rpc()
+        .block_hash(Some(block.number().into()))
+        .await?;
+    if block.number() > finalized_number || canonical_hash != Some(block_hash) {
+        return Err(AppError::Payment("Payment block is not finalized"));
+    }
+
+    for extrinsic in block.body().await?.extrinsics().iter() {
+        let extrinsic = extrinsic?;
+        if <KiltConfig as Config>::Hasher::hash(extrinsic.bytes()) != tx_hash {
+            continue;
+        }
+
+        let events = extrinsic.events().await?;
+        let mut transfers = Vec::new();
+        for transfer in events.find::<runtime::balances::events::Transfer>() {
+            let transfer = transfer?;
+            transfers.push((transfer.to, transfer.amount));
+        }
+
+        return Ok(Some(ExtrinsicTransfers {
+            success: events.has::<runtime::system::events::ExtrinsicSuccess>()?,
+            transfers,
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Checks that the extrinsic `tx_hash` in the finalized block `block_hash` succeeded
+/// and transferred at least `amount` femto KILT to `recipient`.
+pub async fn verify_payment(
+    block_hash: H256,
+    tx_hash: H256,
+    recipient: &AccountId32,
+    amount: u128,
+    chain: &dyn Chain,
+) -> Result<(), AppError> {
+    let extrinsic = chain
+        .get_extrinsic_transfers(block_hash, tx_hash)
+        .await?
+        .ok_or(AppError::Payment("Payment transaction not found"))?;
+
+    if !extrinsic.success {
+        return Err(AppError::Payment("Payment transaction failed"));
+    }
+
+    let transferred = extrinsic
+        .transfers
+        .iter()
+        .filter(|(to, _)| to == recipient)
+        .fold(0u128, |sum, (_, amount)| sum.saturating_add(*amount));
+
+    if transferred < amount {
+        return Err(AppError::Payment("Payment amount is too low"));
+    }
+
+    Ok(())
+}
+
+/// Returns the free balance of the account in femto KILT.
+pub async fn get_free_balance(
+    account: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<u128, AppError> {
+    let account_info = chain_client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch_or_default(&runtime::storage().system().account(account))
+        .await?;
+    Ok(account_info.data.free)
+}
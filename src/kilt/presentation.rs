@@ -0,0 +1,195 @@
+//! Verification of credential presentations which are received in `submit-credential` messages
+//! and of the legitimations which claimers embed into their credentials.
+
+use subxt::{ext::sp_core::H256, OnlineClient};
+
+use crate::{
+    database::dto::{CTypeRequest, Credential, CredentialPresentation, VerifiedCredential},
+    error::AppError,
+    kilt::{
+        credential::verify_credential_hashes, did::get_did_uri, did::verify_did_signature, runtime,
+        Chain, KiltConfig, OnChainAttestation,
+    },
+};
+
+fn parse_hash(hash: &str) -> Result<H256, AppError> {
+    let bytes = hex::decode(hash.trim_start_matches("0x"))?;
+    if bytes.len() != 32 {
+        return Err(AppError::Credential(
+            "Hash is expected to have 32 bytes".to_string(),
+        ));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+/// Returns the data the claimer signs to present a credential. Like `makeSigningData` of the SDK,
+/// the challenge is appended to the root hash as bytes if it is hex encoded.
+pub fn get_presentation_signing_data(
+    root_hash: &str,
+    challenge: &str,
+) -> Result<Vec<u8>, AppError> {
+    let mut signed_data = parse_hash(root_hash)?.as_bytes().to_vec();
+    match challenge.strip_prefix("0x").map(hex::decode) {
+        Some(Ok(challenge)) => signed_data.extend_from_slice(&challenge),
+        _ => signed_data.extend_from_slice(challenge.as_bytes()),
+    }
+    Ok(signed_data)
+}
+
+/// Checks the required properties and the hashes of the credential.
+fn verify_contents(credential: &Credential, request: &CTypeRequest) -> Result<(), AppError> {
+    for property in request.required_properties.iter().flatten() {
+        if credential.claim.contents.get(property).is_none() {
+            return Err(AppError::Credential(format!(
+                "Required property {} is missing",
+                property
+            )));
+        }
+    }
+
+    verify_credential_hashes(credential)
+}
+
+/// Returns the attestation of the claim hash from the storage of the chain.
+pub async fn get_attestation(
+    claim_hash: H256,
+    chain_client: &OnlineClient<KiltConfig>,
+) -> Result<Option<OnChainAttestation>, AppError> {
+    let attestation_key = runtime::storage().attestation().attestations(claim_hash);
+    let attestation = chain_client
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(&attestation_key)
+        .await?;
+
+    Ok(attestation.map(|attestation| OnChainAttestation {
+        ctype_hash: attestation.ctype_hash,
+        attester: attestation.attester,
+        revoked: attestation.revoked,
+    }))
+}
+
+/// Checks that the credential is attested on chain for its CType, is not revoked and that the
+/// attester is trusted. Returns the DID of the attester.
+async fn verify_attestation(
+    credential: &Credential,
+    request: &CTypeRequest,
+    chain: &dyn Chain,
+) -> Result<String, AppError> {
+    let attestation = chain
+        .get_attestation(parse_hash(&credential.root_hash)?)
+        .await?
+        .ok_or(AppError::Credential(
+            "Credential is not attested".to_string(),
+        ))?;
+
+    if attestation.revoked {
+        return Err(AppError::Credential("Credential is revoked".to_string()));
+    }
+
+    if attestation.ctype_hash != parse_hash(&credential.claim.ctype_hash)? {
+        return Err(AppError::Credential(
+            "Credential was attested for another CType".to_string(),
+        ));
+    }
+
+    let attester = get_did_uri(&attestation.attester);
+    if let Some(trusted_attesters) = &request.trusted_attesters {
+        if !trusted_attesters.contains(&attester) {
+            return Err(AppError::Credential(
+                "Credential was not attested by a trusted attester".to_string(),
+            ));
+        }
+    }
+
+    Ok(attester)
+}
+
+/// Verifies the hashes, the claimer signature over the challenge and the on-chain attestation
+/// of a presentation against the requested CTypes.
+pub async fn verify_presentation(
+    presentation: &CredentialPresentation,
+    challenge: &str,
+    requests: &[CTypeRequest],
+    chain: &dyn Chain,
+) -> Result<VerifiedCredential, AppError> {
+    let credential = &presentation.credential;
+    let claim = &credential.claim;
+
+    let request = requests
+        .iter()
+        .find(|request| request.ctype_hash.eq_ignore_ascii_case(&claim.ctype_hash))
+        .ok_or(AppError::Credential(format!(
+            "CType {} was not requested",
+            claim.ctype_hash
+        )))?;
+
+    verify_contents(credential, request)?;
+
+    if presentation.claimer_signature.challenge.as_deref() != Some(challenge) {
+        return Err(AppError::Credential(
+            "Presentation does not answer the challenge".to_string(),
+        ));
+    }
+
+    let signed_data = get_presentation_signing_data(&credential.root_hash, challenge)?;
+
+    verify_did_signature(
+        &signed_data,
+        &presentation.claimer_signature.signature,
+        &presentation.claimer_signature.key_uri,
+        &claim.owner,
+        chain,
+    )
+    .await
+    .map_err(|e| match e {
+        AppError::Did(_) | AppError::LightDid(_) => {
+            AppError::Credential("Claimer signature is invalid".to_string())
+        }
+        e => e,
+    })?;
+
+    let attester = verify_attestation(credential, request, chain).await?;
+
+    Ok(VerifiedCredential {
+        root_hash: credential.root_hash.clone(),
+        claim: claim.clone(),
+        attester,
+    })
+}
+
+/// Checks that the credential embeds a valid legitimation of the same owner for every required CType.
+pub async fn verify_legitimations(
+    credential: &Credential,
+    required_legitimations: &[CTypeRequest],
+    chain: &dyn Chain,
+) -> Result<(), AppError> {
+    let legitimations = credential.legitimations.as_deref().unwrap_or_default();
+
+    for request in required_legitimations {
+        let legitimation = legitimations
+            .iter()
+            .find(|legitimation| {
+                legitimation
+                    .claim
+                    .ctype_hash
+                    .eq_ignore_ascii_case(&request.ctype_hash)
+            })
+            .ok_or(AppError::Credential(format!(
+                "Legitimation of CType {} is missing",
+                request.ctype_hash
+            )))?;
+
+        if legitimation.claim.owner != credential.claim.owner {
+            return Err(AppError::Credential(
+                "Legitimation belongs to another claimer".to_string(),
+            ));
+        }
+
+        verify_contents(legitimation, request)?;
+        verify_attestation(legitimation, request, chain).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! Quotes which are attached to the terms of the credential API.
+//! The attester signs the quote with its authentication key and the claimer accepts it by
+//! signing the attester signed quote together with the root hash of the requested credential and
+//! its own DID.
+//! Signatures are created over the blake2b-256 hash of the JSON encoded object with sorted keys.
+
+use blake2::{Blake2b, Digest};
+use hmac::digest::typenum::U32;
+use serde::Serialize;
+use subxt::{
+    ext::sp_core::{sr25519, Pair},
+    tx::PairSigner,
+};
+
+use crate::{
+    configuration::QuoteConfig,
+    database::dto::{AttesterSignedQuote, DidSignature, Quote, QuoteAgreement},
+    error::AppError,
+    kilt::{did::verify_did_signature, Chain, KiltConfig},
+};
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Decimal places of the KILT token.
+const KILT_DECIMALS: usize = 15;
+
+/// Sorts the keys of all nested objects, since objects keep their insertion order.
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut entries: Vec<_> = object.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(sort_keys).collect())
+        }
+        value => value,
+    }
+}
+
+fn hash_object<T: Serialize>(object: &T) -> Result<Vec<u8>, AppError> {
+    let normalized = serde_json::to_string(&sort_keys(serde_json::to_value(object)?))?;
+    let mut hasher = Blake2b256::new();
+    hasher.update(normalized);
+    Ok(hasher.finalize().to_vec())
+}
+
+pub fn create_quote(config: &QuoteConfig, attester_did: &str, ctype_hash: &str) -> Quote {
+    Quote {
+        attester_did: attester_did.to_string(),
+        ctype_hash: ctype_hash.to_string(),
+        cost: config.cost.clone(),
+        currency: config.currency.clone(),
+        timeframe: config.timeframe.clone(),
+        terms_and_conditions: config.terms_and_conditions.clone(),
+    }
+}
+
+pub fn sign_quote(
+    quote: Quote,
+    key_uri: &str,
+    signer: &PairSigner<KiltConfig, sr25519::Pair>,
+) -> Result<AttesterSignedQuote, AppError> {
+    let signature = signer.signer().sign(&hash_object(&quote)?);
+    Ok(AttesterSignedQuote {
+        quote,
+        attester_signature: DidSignature {
+            key_uri: key_uri.to_string(),
+            signature: format!("0x{}", hex::encode(signature)),
+        },
+    })
+}
+
+/// Checks that the quote was signed by the given authentication key of the attester.
+pub fn verify_attester_signature(
+    signed_quote: &AttesterSignedQuote,
+    signer: &PairSigner<KiltConfig, sr25519::Pair>,
+) -> Result<(), AppError> {
+    let signature = hex::decode(
+        signed_quote
+            .attester_signature
+            .signature
+            .trim_start_matches("0x"),
+    )
+    .map_err(|_| AppError::Quote("Attester signature isn't valid hex"))?;
+    let signature = sr25519::Signature::from_slice(&signature)
+        .ok_or(AppError::Quote("Attester signature is malformed"))?;
+
+    if !sr25519::Pair::verify(
+        &signature,
+        hash_object(&signed_quote.quote)?,
+        &signer.signer().public(),
+    ) {
+        return Err(AppError::Quote("Attester signature is invalid"));
+    }
+
+    Ok(())
+}
+
+/// The part of a quote agreement which is signed by the claimer.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnsignedQuoteAgreement<'a> {
+    #[serde(flatten)]
+    attester_signed_quote: &'a AttesterSignedQuote,
+    root_hash: &'a str,
+    claimer_did: &'a str,
+}
+
+/// Returns the data the claimer signs to accept the quote, which binds the attester signed quote
+/// to the credential and the claimer.
+pub fn get_quote_agreement_data(agreement: &QuoteAgreement) -> Result<Vec<u8>, AppError> {
+    hash_object(&UnsignedQuoteAgreement {
+        attester_signed_quote: &agreement.attester_signed_quote,
+        root_hash: &agreement.root_hash,
+        claimer_did: &agreement.claimer_did,
+    })
+}
+
+/// Checks that the claimer accepted the attester signed quote for the given credential.
+pub async fn verify_quote_agreement(
+    agreement: &QuoteAgreement,
+    root_hash: &str,
+    claimer_did: &str,
+    chain: &dyn Chain,
+) -> Result<(), AppError> {
+    if agreement.root_hash.to_lowercase() != root_hash.to_lowercase() {
+        return Err(AppError::Quote("Quote was accepted for another credential"));
+    }
+
+    if agreement.claimer_did != claimer_did {
+        return Err(AppError::Quote("Quote was accepted by another claimer"));
+    }
+
+    verify_did_signature(
+        &get_quote_agreement_data(agreement)?,
+        &agreement.claimer_signature.signature,
+        &agreement.claimer_signature.key_uri,
+        claimer_did,
+        chain,
+    )
+    .await
+    .map_err(|e| match e {
+        AppError::Did(_) => AppError::Quote("Claimer signature is invalid"),
+        e => e,
+    })
+}
+
+/// Converts an amount of KILT into femto KILT without going through floating point numbers.
+pub fn kilt_to_femto(amount: &serde_json::Number) -> Option<u128> {
+    let amount = amount.to_string();
+    let (integer, fraction) = amount.split_once('.').unwrap_or((&amount, ""));
+    if fraction.len() > KILT_DECIMALS || integer.starts_with('-') {
+        return None;
+    }
+    let fraction = format!("{:0<width$}", fraction, width = KILT_DECIMALS);
+    integer
+        .parse::<u128>()
+        .ok()?
+        .checked_mul(10u128.pow(KILT_DECIMALS as u32))?
+        .checked_add(fraction.parse::<u128>().ok()?)
+}
+
+/// Formats an amount of femto KILT as KILT.
+pub fn femto_to_kilt(amount: u128) -> String {
+    let unit = 10u128.pow(KILT_DECIMALS as u32);
+    let fraction = format!("{:0>width$}", amount % unit, width = KILT_DECIMALS);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (amount / unit).to_string()
+    } else {
+        format!("{}.{}", amount / unit, fraction)
+    }
+}
@@ -0,0 +1,531 @@
+use subxt::{
+    ext::sp_core::{sr25519, Pair, H256},
+    tx::PairSigner,
+    utils::AccountId32,
+};
+
+use crate::{
+    configuration::QuoteConfig,
+    database::dto::{
+        CTypeRequest, Credential, DelegationPermissions, DidSignature, QuoteAgreement,
+    },
+    error::AppError,
+    kilt::{
+        credential::{calculate_root_hash, verify_credential_hashes},
+        delegation::{get_delegation_creation_hash, verify_delegation_node},
+        get_did_uri,
+        payment::verify_payment,
+        presentation::{get_presentation_signing_data, verify_legitimations},
+        quote::{
+            create_quote, femto_to_kilt, get_quote_agreement_data, kilt_to_femto, sign_quote,
+            verify_attester_signature,
+        },
+        Chain, ExtrinsicTransfers, KiltConfig, MockChain, OnChainDelegationNode,
+    },
+};
+
+fn get_valid_credential() -> Credential {
+    // Create a Credential object whose hashes commit to its claim.
+    let credential_json = serde_json::json!({
+        "claim": {
+            "cTypeHash": "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac",
+            "contents": {
+                "Email": "hello@kilt.io"
+            },
+            "owner": "did:kilt:4qBmSXvzSYCkTnCyqtE62KhNLrvUKvtxmkwJNQrRdMztpT1r"
+        },
+        "claimHashes": [
+            "0x22375cc1baba0ef8d7da11bd1b158a98034161ff74fe866fe192085fc0f03ffc",
+            "0x904c89de0da7ab1a53751ed8ff982ada61dfa1ba7ef2addff5d1260739543db5"
+        ],
+        "claimNonceMap": {
+            "0xb63271bb51c47bcca99b0f03c8cade56263c9eb31c9858ffa0a34a89f8523be4": "5f25a0d1-b68f-4e06-a003-26c391935540",
+            "0xf2aba36566e0f998683a172effe6443adb9cdd52f63ef264faeab13e4e41f1b7": "c57e9c72-fa8a-4e4f-b60f-a20234317bda"
+        },
+        "rootHash": "0x63acce746b1b9063d20e5de1aa39f02ff9123878d920fafcba0fc30f5ccb00ec",
+        "legitimations": []
+    });
+
+    serde_json::from_value::<Credential>(credential_json).unwrap()
+}
+
+#[test]
+fn test_calculate_root_hash() {
+    let credential = get_valid_credential();
+
+    let root_hash = calculate_root_hash(&credential).expect("Hashing should not fail");
+
+    assert_eq!(root_hash, credential.root_hash);
+}
+
+#[test]
+fn test_verify_credential_hashes_valid() {
+    let credential = get_valid_credential();
+
+    assert!(verify_credential_hashes(&credential).is_ok());
+}
+
+#[test]
+fn test_verify_credential_hashes_modified_contents() {
+    // Arrange: Change the claim contents without updating the hashes.
+    let mut credential = get_valid_credential();
+    credential.claim.contents = serde_json::json!({ "Email": "info@kilt.io" });
+
+    // Assert: Check that the modified claim is detected.
+    assert!(verify_credential_hashes(&credential).is_err());
+}
+
+#[test]
+fn test_verify_credential_hashes_nested_contents() {
+    // Arrange: Nested claim properties are hashed in insertion order, like `JSON.stringify` does.
+    let credential_json = serde_json::json!({
+        "claim": {
+            "cTypeHash": "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac",
+            "contents": {
+                "Address": {
+                    "Street": "Zimmerstraße 67",
+                    "City": "Berlin"
+                }
+            },
+            "owner": "did:kilt:4qBmSXvzSYCkTnCyqtE62KhNLrvUKvtxmkwJNQrRdMztpT1r"
+        },
+        "claimHashes": [
+            "0x9a7dc17eee8e35d055449cac0376b2323a1d0d26329d4077582fa489b5c68185",
+            "0xf8146be366f23bd1ed1348a9c9357f29596a898c7b3a1b06787af9b4d7334c7b"
+        ],
+        "claimNonceMap": {
+            "0xb63271bb51c47bcca99b0f03c8cade56263c9eb31c9858ffa0a34a89f8523be4": "3f6d0e2a-8c1b-4f5e-9a7d-2b4c6e8f0a1c",
+            "0xf1cc62d39c629188744a8f135f9516f7c20c8e516bd0e8e0dc271c0af1856d73": "a9b8c7d6-e5f4-4a3b-8c2d-1e0f9a8b7c6d"
+        },
+        "rootHash": "0x31150dd826a2ccb1d58110a536ef835567dc9bac6155ec963d0438a799141efc",
+        "legitimations": []
+    });
+    let credential = serde_json::from_value::<Credential>(credential_json).unwrap();
+
+    // Assert: Check that the hashes of the nested claim are accepted.
+    assert!(verify_credential_hashes(&credential).is_ok());
+}
+
+#[test]
+fn test_verify_credential_hashes_wrong_root_hash() {
+    // Arrange: Replace the root hash.
+    let mut credential = get_valid_credential();
+    credential.root_hash =
+        "0xf69ce26ca50b5d5f38cd32a99d031cd52fff42f17b9afb32895ffba260fb616a".to_string();
+
+    // Assert: Check that the wrong root hash is detected.
+    assert!(verify_credential_hashes(&credential).is_err());
+}
+
+fn get_quote_config() -> QuoteConfig {
+    serde_json::from_value(serde_json::json!({
+        "cost": { "net": 10, "gross": 11.9, "tax": { "VAT": 1.9 } },
+        "currency": "KILT",
+        "timeframe": "2024-12-31T23:59:59.000Z",
+        "termsAndConditions": "https://example.com/terms"
+    }))
+    .expect("Parsing quote config should not fail")
+}
+
+fn get_signer(seed: &str) -> PairSigner<KiltConfig, sr25519::Pair> {
+    PairSigner::new(sr25519::Pair::from_string(seed, None).expect("Creating pair should not fail"))
+}
+
+#[test]
+fn test_sign_quote() {
+    // Arrange: Create a quote for the attester.
+    let signer = get_signer("//Alice");
+    let quote = create_quote(
+        &get_quote_config(),
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH",
+        "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac",
+    );
+
+    // Act: Sign the quote.
+    let signed_quote = sign_quote(
+        quote,
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH#0x01",
+        &signer,
+    )
+    .expect("Signing should not fail");
+
+    // Assert: Check that the signature is valid and the quote is serialized as expected.
+    assert!(verify_attester_signature(&signed_quote, &signer).is_ok());
+    let json = serde_json::to_value(&signed_quote).expect("Serializing should not fail");
+    assert_eq!(json["cost"]["gross"], serde_json::json!(11.9));
+    assert_eq!(
+        json["cTypeHash"],
+        "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac"
+    );
+    assert!(json["attesterSignature"]["keyUri"].is_string());
+}
+
+#[test]
+fn test_verify_attester_signature_modified_quote() {
+    // Arrange: Sign a quote and lower its cost afterwards.
+    let signer = get_signer("//Alice");
+    let quote = create_quote(
+        &get_quote_config(),
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH",
+        "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac",
+    );
+    let mut signed_quote = sign_quote(
+        quote,
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH#0x01",
+        &signer,
+    )
+    .expect("Signing should not fail");
+    signed_quote.quote.cost.gross = serde_json::Number::from(1);
+
+    // Assert: Check that the modification and a foreign signer are detected.
+    assert!(verify_attester_signature(&signed_quote, &signer).is_err());
+    assert!(verify_attester_signature(&signed_quote, &get_signer("//Bob")).is_err());
+}
+
+#[test]
+fn test_quote_agreement_data_swapped_root_hash() {
+    // Arrange: The claimer accepts a signed quote for a credential.
+    let attester = get_signer("//Alice");
+    let claimer = get_signer("//Bob");
+    let quote = create_quote(
+        &get_quote_config(),
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH",
+        "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac",
+    );
+    let attester_signed_quote = sign_quote(
+        quote,
+        "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH#0x01",
+        &attester,
+    )
+    .expect("Signing should not fail");
+    let mut agreement = QuoteAgreement {
+        attester_signed_quote,
+        root_hash: "0x63acce746b1b9063d20e5de1aa39f02ff9123878d920fafcba0fc30f5ccb00ec".to_string(),
+        claimer_did: "did:kilt:4qBmSXvzSYCkTnCyqtE62KhNLrvUKvtxmkwJNQrRdMztpT1r".to_string(),
+        claimer_signature: DidSignature {
+            key_uri: "did:kilt:4qBmSXvzSYCkTnCyqtE62KhNLrvUKvtxmkwJNQrRdMztpT1r#0x01".to_string(),
+            signature: String::new(),
+        },
+    };
+    let data = get_quote_agreement_data(&agreement).expect("Hashing should not fail");
+    let signature = claimer.signer().sign(&data);
+
+    // Act: Replace the root hash of the accepted credential.
+    agreement.root_hash =
+        "0xf69ce26ca50b5d5f38cd32a99d031cd52fff42f17b9afb32895ffba260fb616a".to_string();
+    let swapped_data = get_quote_agreement_data(&agreement).expect("Hashing should not fail");
+
+    // Assert: Check that the signature does not cover the other credential.
+    let public = claimer.signer().public();
+    assert!(sr25519::Pair::verify(&signature, &data, &public));
+    assert!(!sr25519::Pair::verify(&signature, &swapped_data, &public));
+}
+
+#[test]
+fn test_presentation_signing_data() {
+    // Arrange: Sign the root hash and the decoded challenge like the SDK does.
+    let claimer = get_signer("//Bob");
+    let root_hash = "0x63acce746b1b9063d20e5de1aa39f02ff9123878d920fafcba0fc30f5ccb00ec";
+    let challenge = "0x1f5a9c3e7b2d4f6a8c0e1b3d5f7a9c2e4b6d8f0a1c3e5b7d9f2a4c6e8b0d1f3a";
+    let mut sdk_data = hex::decode(root_hash.trim_start_matches("0x")).unwrap();
+    sdk_data.extend(hex::decode(challenge.trim_start_matches("0x")).unwrap());
+    let signature = claimer.signer().sign(&sdk_data);
+
+    // Act
+    let data =
+        get_presentation_signing_data(root_hash, challenge).expect("Hashing should not fail");
+
+    // Assert: Check that the signature of the wallet is accepted.
+    assert_eq!(data.len(), 64);
+    assert!(sr25519::Pair::verify(
+        &signature,
+        &data,
+        &claimer.signer().public()
+    ));
+}
+
+#[test]
+fn test_kilt_to_femto() {
+    assert_eq!(
+        kilt_to_femto(&serde_json::Number::from(10)),
+        Some(10_000_000_000_000_000)
+    );
+    assert_eq!(
+        kilt_to_femto(&serde_json::Number::from_f64(11.9).unwrap()),
+        Some(11_900_000_000_000_000)
+    );
+    assert_eq!(kilt_to_femto(&serde_json::Number::from(-1)), None);
+}
+
+#[test]
+fn test_femto_to_kilt() {
+    assert_eq!(femto_to_kilt(10_000_000_000_000_000), "10");
+    assert_eq!(femto_to_kilt(11_900_000_000_000_000), "11.9");
+    assert_eq!(femto_to_kilt(1), "0.000000000000001");
+}
+
+#[test]
+fn test_delegation_permissions() {
+    let permissions = DelegationPermissions {
+        attest: true,
+        delegate: true,
+    };
+    let bits: u32 = permissions.into();
+
+    assert_eq!(bits, 3);
+    assert_eq!(DelegationPermissions::from(bits), permissions);
+    assert_eq!(
+        DelegationPermissions::from(2),
+        DelegationPermissions {
+            attest: false,
+            delegate: true,
+        }
+    );
+}
+
+#[test]
+fn test_get_delegation_creation_hash() {
+    let hash = get_delegation_creation_hash(
+        H256::repeat_byte(1),
+        H256::repeat_byte(2),
+        H256::repeat_byte(3),
+        1,
+    );
+
+    assert_eq!(
+        format!("{:?}", hash),
+        "0x9945034cd6ee28e2a7a12ed1345bac97974899779dc588ee51bd3c0d48f06036"
+    );
+}
+
+/// Returns a credential which embeds the valid credential as legitimation of its CType.
+fn get_legitimated_credential() -> (Credential, CTypeRequest) {
+    let legitimation = get_valid_credential();
+    let request = CTypeRequest {
+        ctype_hash: legitimation.claim.ctype_hash.clone(),
+        trusted_attesters: None,
+        required_properties: None,
+    };
+    let mut credential = get_valid_credential();
+    credential.legitimations = Some(vec![legitimation]);
+    (credential, request)
+}
+
+fn parse_hash(hash: &str) -> H256 {
+    H256::from_slice(&hex::decode(hash.trim_start_matches("0x")).unwrap())
+}
+
+#[tokio::test]
+async fn test_verify_legitimations() {
+    // Arrange: Attest the legitimation on the in-memory chain.
+    let attester = AccountId32(get_signer("//Alice").signer().public().0);
+    let chain = MockChain::new(&attester);
+    let (credential, request) = get_legitimated_credential();
+    let legitimation = &credential.legitimations.as_ref().unwrap()[0];
+    let not_attested =
+        verify_legitimations(&credential, std::slice::from_ref(&request), &chain).await;
+    chain
+        .create_claim(
+            parse_hash(&legitimation.root_hash),
+            parse_hash(&legitimation.claim.ctype_hash),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Act
+    let attested = verify_legitimations(&credential, std::slice::from_ref(&request), &chain).await;
+    let untrusted = verify_legitimations(
+        &credential,
+        &[CTypeRequest {
+            trusted_attesters: Some(vec![get_did_uri(&AccountId32([1; 32]))]),
+            ..request.clone()
+        }],
+        &chain,
+    )
+    .await;
+    chain
+        .revoke_claim(parse_hash(&legitimation.root_hash))
+        .await
+        .unwrap();
+    let revoked = verify_legitimations(&credential, &[request], &chain).await;
+
+    // Assert: Check that only an attested legitimation of a trusted attester is accepted.
+    assert!(matches!(not_attested, Err(AppError::Credential(_))));
+    assert!(attested.is_ok());
+    assert!(matches!(untrusted, Err(AppError::Credential(_))));
+    assert!(matches!(revoked, Err(AppError::Credential(_))));
+}
+
+#[tokio::test]
+async fn test_verify_legitimations_modified_contents() {
+    // Arrange: Attest the legitimation and change its contents afterwards.
+    let attester = AccountId32(get_signer("//Alice").signer().public().0);
+    let chain = MockChain::new(&attester);
+    let (mut credential, request) = get_legitimated_credential();
+    let legitimation = &mut credential.legitimations.as_mut().unwrap()[0];
+    chain
+        .create_claim(
+            parse_hash(&legitimation.root_hash),
+            parse_hash(&legitimation.claim.ctype_hash),
+            None,
+        )
+        .await
+        .unwrap();
+    legitimation.claim.contents = serde_json::json!({ "Email": "info@kilt.io" });
+
+    // Act
+    let result = verify_legitimations(&credential, &[request], &chain).await;
+
+    // Assert: Check that the hashes of the legitimation are verified.
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_verify_payment() {
+    // Arrange: Add a transfer of 10 KILT in two parts to the in-memory chain.
+    let chain = MockChain::new(&AccountId32([0; 32]));
+    let recipient = AccountId32([1; 32]);
+    let block_hash = H256::repeat_byte(1);
+    let tx_hash = H256::repeat_byte(2);
+    let failed_tx_hash = H256::repeat_byte(3);
+    let amount = kilt_to_femto(&serde_json::Number::from(10)).unwrap();
+    chain.add_extrinsic(
+        block_hash,
+        tx_hash,
+        ExtrinsicTransfers {
+            success: true,
+            transfers: vec![
+                (recipient.clone(), amount / 2),
+                (AccountId32([2; 32]), amount),
+                (recipient.clone(), amount / 2),
+            ],
+        },
+    );
+    chain.add_extrinsic(
+        block_hash,
+        failed_tx_hash,
+        ExtrinsicTransfers {
+            success: false,
+            transfers: vec![(recipient.clone(), amount)],
+        },
+    );
+
+    // Act
+    let paid = verify_payment(block_hash, tx_hash, &recipient, amount, &chain).await;
+    let too_low = verify_payment(block_hash, tx_hash, &recipient, amount + 1, &chain).await;
+    let failed = verify_payment(block_hash, failed_tx_hash, &recipient, amount, &chain).await;
+    let not_found = verify_payment(tx_hash, block_hash, &recipient, amount, &chain).await;
+
+    // Assert: Check that only the transfers to the recipient of a successful extrinsic count.
+    assert!(paid.is_ok());
+    assert!(matches!(too_low, Err(AppError::Payment(_))));
+    assert!(matches!(failed, Err(AppError::Payment(_))));
+    assert!(matches!(not_found, Err(AppError::Payment(_))));
+}
+
+/// Adds a hierarchy for the CType of the valid credential, whose root is owned by the attester
+/// and has a child node of the attester which may attest.
+fn add_delegation_hierarchy(chain: &MockChain, attester: &AccountId32) -> (H256, H256) {
+    let root_id = H256::repeat_byte(5);
+    let node_id = H256::repeat_byte(6);
+    let ctype_hash = parse_hash(&get_valid_credential().claim.ctype_hash);
+    chain.add_delegation_hierarchy(root_id, ctype_hash);
+    chain.add_delegation_node(
+        root_id,
+        OnChainDelegationNode {
+            hierarchy_root_id: root_id,
+            parent: None,
+            owner: attester.clone(),
+            permissions: u32::from(DelegationPermissions {
+                attest: false,
+                delegate: true,
+            }),
+            revoked: false,
+        },
+    );
+    chain.add_delegation_node(
+        node_id,
+        OnChainDelegationNode {
+            hierarchy_root_id: root_id,
+            parent: Some(root_id),
+            owner: attester.clone(),
+            permissions: u32::from(DelegationPermissions {
+                attest: true,
+                delegate: false,
+            }),
+            revoked: false,
+        },
+    );
+    (root_id, node_id)
+}
+
+#[tokio::test]
+async fn test_verify_delegation_node() {
+    // Arrange
+    let attester = AccountId32(get_signer("//Alice").signer().public().0);
+    let chain = MockChain::new(&attester);
+    let (root_id, node_id) = add_delegation_hierarchy(&chain, &attester);
+    let ctype_hash = parse_hash(&get_valid_credential().claim.ctype_hash);
+
+    // Act
+    let delegated = verify_delegation_node(node_id, &attester, ctype_hash, &chain).await;
+    let root = verify_delegation_node(root_id, &attester, ctype_hash, &chain).await;
+    let other_attester =
+        verify_delegation_node(node_id, &AccountId32([1; 32]), ctype_hash, &chain).await;
+
+    // Assert: Check that only the node of the attester which may attest is accepted.
+    assert!(delegated.is_ok());
+    assert!(matches!(root, Err(AppError::Delegation(_))));
+    assert!(matches!(other_attester, Err(AppError::Delegation(_))));
+}
+
+#[tokio::test]
+async fn test_verify_delegation_node_other_ctype() {
+    // Arrange
+    let attester = AccountId32(get_signer("//Alice").signer().public().0);
+    let chain = MockChain::new(&attester);
+    let (_, node_id) = add_delegation_hierarchy(&chain, &attester);
+
+    // Act: Attest another CType under the delegation node.
+    let result = verify_delegation_node(node_id, &attester, H256::repeat_byte(7), &chain).await;
+
+    // Assert
+    assert!(matches!(
+        result,
+        Err(AppError::Delegation(
+            "Delegation hierarchy was created for another CType"
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_verify_delegation_node_revoked_parent() {
+    // Arrange: Revoke the root of the hierarchy.
+    let attester = AccountId32(get_signer("//Alice").signer().public().0);
+    let chain = MockChain::new(&attester);
+    let (root_id, node_id) = add_delegation_hierarchy(&chain, &attester);
+    let ctype_hash = parse_hash(&get_valid_credential().claim.ctype_hash);
+    chain.add_delegation_node(
+        root_id,
+        OnChainDelegationNode {
+            hierarchy_root_id: root_id,
+            parent: None,
+            owner: attester.clone(),
+            permissions: u32::from(DelegationPermissions {
+                attest: false,
+                delegate: true,
+            }),
+            revoked: true,
+        },
+    );
+
+    // Act
+    let result = verify_delegation_node(node_id, &attester, ctype_hash, &chain).await;
+
+    // Assert: Check that the nodes below a revoked node can not attest.
+    assert!(matches!(
+        result,
+        Err(AppError::Delegation("Delegation node is revoked"))
+    ));
+}
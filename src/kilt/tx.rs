@@ -1,31 +1,39 @@
 use parity_scale_codec::Encode;
-use subxt::{ext::sp_core, tx::PairSigner, utils::AccountId32, OnlineClient};
+use std::time::Instant;
+use subxt::{
+    blocks::ExtrinsicEvents, ext::sp_core, tx::PairSigner, utils::AccountId32, OnlineClient,
+};
 
-use crate::kilt::{
-    runtime,
-    utils::{calculate_signature, get_current_block, get_next_tx_counter},
-    KiltConfig, RuntimeCall,
+use crate::{
+    kilt::{
+        runtime,
+        utils::{calculate_signature, get_current_block, get_next_tx_counter},
+        DidKeys, DidUpdate, KiltConfig, RuntimeCall,
+    },
+    metrics::observe_extrinsic,
 };
 
 use runtime::runtime_types;
-use runtime::runtime_types::did::did_details::DidAuthorizedCallOperation;
+use runtime::runtime_types::bounded_collections::bounded_btree_set::BoundedBTreeSet;
+use runtime::runtime_types::delegation::access_control::DelegationAc;
+use runtime::runtime_types::delegation::delegation_hierarchy::Permissions;
+use runtime::runtime_types::did::did_details::{
+    DidAuthorizedCallOperation, DidCreationDetails, DidEncryptionKey, DidSignature,
+    DidVerificationKey,
+};
+use runtime::runtime_types::runtime_common::authorization::PalletAuthorize;
 
-pub async fn create_claim(
-    claim_hash: sp_core::H256,
-    ctype_hash: sp_core::H256,
+/// Wraps the call into a DID authorized call of `did_address`, submits it and waits until it is finalized.
+/// Returns the encoded DID authorized call together with the events of the extrinsic.
+async fn submit_did_call(
+    call: RuntimeCall,
     did_address: &AccountId32,
     chain_client: &OnlineClient<KiltConfig>,
     payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
     signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
-) -> Result<Vec<u8>, subxt::Error> {
-    let tx_counter = get_next_tx_counter(&chain_client, &did_address).await?;
-    let block_number = get_current_block(&chain_client).await?;
-
-    let call = RuntimeCall::Attestation(runtime_types::attestation::pallet::Call::add {
-        claim_hash,
-        ctype_hash,
-        authorization: None,
-    });
+) -> Result<(Vec<u8>, ExtrinsicEvents<KiltConfig>), subxt::Error> {
+    let tx_counter = get_next_tx_counter(chain_client, did_address).await?;
+    let block_number = get_current_block(chain_client).await?;
 
     let did_call = DidAuthorizedCallOperation {
         did: did_address.to_owned(),
@@ -37,7 +45,7 @@ pub async fn create_claim(
 
     let encoded_call = did_call.encode();
 
-    let signature = calculate_signature(&did_call.encode(), signer);
+    let signature = calculate_signature(&encoded_call, signer);
     let final_tx = runtime::tx().did().submit_did_call(did_call, signature);
     let events = chain_client
         .tx()
@@ -46,70 +54,271 @@ pub async fn create_claim(
         .wait_for_finalized_success()
         .await?;
 
-    let created_event = events.find_first::<runtime::attestation::events::AttestationCreated>()?;
+    Ok((encoded_call, events))
+}
 
-    match created_event {
+pub async fn create_claim(
+    claim_hash: sp_core::H256,
+    ctype_hash: sp_core::H256,
+    delegation_id: Option<sp_core::H256>,
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+    payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+    signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+) -> Result<Vec<u8>, subxt::Error> {
+    let started_at = Instant::now();
+    let result = async {
+        let authorization = match delegation_id {
+            Some(subject_node_id) => Some(PalletAuthorize::Delegation(DelegationAc {
+                subject_node_id,
+                max_checks: chain_client
+                    .constants()
+                    .at(&runtime::constants().delegation().max_parent_checks())?,
+                __subxt_unused_type_params: Default::default(),
+            })),
+            None => None,
+        };
+
+        let call = RuntimeCall::Attestation(runtime_types::attestation::pallet::Call::add {
+            claim_hash,
+            ctype_hash,
+            authorization,
+        });
+
+        let (encoded_call, events) =
+            submit_did_call(call, did_address, chain_client, payer, signer).await?;
+
+        let created_event =
+            events.find_first::<runtime::attestation::events::AttestationCreated>()?;
+
+        match created_event {
+            Some(_) => {
+                log::info!("Attestation with root hash {:?} created", claim_hash);
+                Ok(encoded_call)
+            }
+            _ => {
+                log::info!(
+                    "Attestation with root hash {:?} could not be created. Create Event not found",
+                    claim_hash
+                );
+                Err(subxt::Error::Other("Created Event not found".to_string()))
+            }
+        }
+    }
+    .await;
+
+    observe_extrinsic("create_claim", started_at, &result);
+    result
+}
+
+pub async fn revoke_claim(
+    claim_hash: sp_core::H256,
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+    payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+    signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+) -> Result<Vec<u8>, subxt::Error> {
+    let started_at = Instant::now();
+    let result = async {
+        let call = RuntimeCall::Attestation(runtime_types::attestation::pallet::Call::revoke {
+            claim_hash,
+            authorization: None,
+        });
+
+        let (encoded_call, events) =
+            submit_did_call(call, did_address, chain_client, payer, signer).await?;
+
+        let revoke_event =
+            events.find_first::<runtime::attestation::events::AttestationRevoked>()?;
+
+        match revoke_event {
+            Some(_) => {
+                log::info!("Attestation with root hash {:?} revoked", claim_hash);
+                Ok(encoded_call)
+            }
+            _ => {
+                log::info!(
+                    "Attestation with root hash {:?} could not be revoked. Revoke Event not found",
+                    claim_hash
+                );
+                Err(subxt::Error::Other("Created Event not found".to_string()))
+            }
+        }
+    }
+    .await;
+
+    observe_extrinsic("revoke_claim", started_at, &result);
+    result
+}
+
+pub async fn create_delegation_hierarchy(
+    root_node_id: sp_core::H256,
+    ctype_hash: sp_core::H256,
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+    payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+    signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+) -> Result<Vec<u8>, subxt::Error> {
+    let call = RuntimeCall::Delegation(runtime_types::delegation::pallet::Call::create_hierarchy {
+        root_node_id,
+        ctype_hash,
+    });
+
+    let (encoded_call, events) =
+        submit_did_call(call, did_address, chain_client, payer, signer).await?;
+
+    match events.find_first::<runtime::delegation::events::HierarchyCreated>()? {
         Some(_) => {
-            log::info!("Attestation with root hash {:?} created", claim_hash);
+            log::info!("Delegation hierarchy {:?} created", root_node_id);
             Ok(encoded_call)
         }
-        _ => {
-            log::info!(
-                "Attestation with root hash {:?} could not be created. Create Event not found",
-                claim_hash
-            );
-            Err(subxt::Error::Other("Created Event not found".to_string()))
+        None => Err(subxt::Error::Other(
+            "HierarchyCreated Event not found".to_string(),
+        )),
+    }
+}
+
+/// Adds a delegation node below `parent_id`. `delegate_signature` is the signature of the delegate
+/// over the delegation creation hash, see [`crate::kilt::get_delegation_creation_hash`].
+#[allow(clippy::too_many_arguments)]
+pub async fn add_delegation(
+    delegation_id: sp_core::H256,
+    parent_id: sp_core::H256,
+    delegate: AccountId32,
+    permissions: u32,
+    delegate_signature: DidSignature,
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+    payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+    signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+) -> Result<Vec<u8>, subxt::Error> {
+    let call = RuntimeCall::Delegation(runtime_types::delegation::pallet::Call::add_delegation {
+        delegation_id,
+        parent_id,
+        delegate,
+        permissions: Permissions { bits: permissions },
+        delegate_signature,
+    });
+
+    let (encoded_call, events) =
+        submit_did_call(call, did_address, chain_client, payer, signer).await?;
+
+    match events.find_first::<runtime::delegation::events::DelegationCreated>()? {
+        Some(_) => {
+            log::info!("Delegation node {:?} created", delegation_id);
+            Ok(encoded_call)
         }
+        None => Err(subxt::Error::Other(
+            "DelegationCreated Event not found".to_string(),
+        )),
     }
 }
 
-pub async fn revoke_claim(
-    claim_hash: sp_core::H256,
+/// Revokes a delegation node together with all of its children. The attester has to own the node
+/// or one of its parents.
+pub async fn revoke_delegation(
+    delegation_id: sp_core::H256,
     did_address: &AccountId32,
     chain_client: &OnlineClient<KiltConfig>,
     payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
     signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
 ) -> Result<Vec<u8>, subxt::Error> {
-    let tx_counter = get_next_tx_counter(&chain_client, &did_address).await?;
-    let block_number = get_current_block(&chain_client).await?;
+    let constants = runtime::constants().delegation();
+    let call =
+        RuntimeCall::Delegation(runtime_types::delegation::pallet::Call::revoke_delegation {
+            delegation_id,
+            max_parent_checks: chain_client
+                .constants()
+                .at(&constants.max_parent_checks())?,
+            max_revocations: chain_client.constants().at(&constants.max_revocations())?,
+        });
 
-    let payer_account = payer.account_id();
+    let (encoded_call, events) =
+        submit_did_call(call, did_address, chain_client, payer, signer).await?;
 
-    let did_call = DidAuthorizedCallOperation {
-        did: did_address.to_owned(),
-        tx_counter,
-        block_number,
-        call: RuntimeCall::Attestation(runtime_types::attestation::pallet::Call::revoke {
-            claim_hash,
-            authorization: None,
-        }),
-        submitter: payer_account.clone().into(),
+    match events.find_first::<runtime::delegation::events::DelegationRevoked>()? {
+        Some(_) => {
+            log::info!("Delegation node {:?} revoked", delegation_id);
+            Ok(encoded_call)
+        }
+        None => Err(subxt::Error::Other(
+            "DelegationRevoked Event not found".to_string(),
+        )),
+    }
+}
+
+/// Updates the keys or service endpoints of the DID. These calls have to be authorized by the
+/// authentication key of the DID.
+pub async fn update_did(
+    update: DidUpdate,
+    did_address: &AccountId32,
+    chain_client: &OnlineClient<KiltConfig>,
+    payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+    authentication_signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+) -> Result<Vec<u8>, subxt::Error> {
+    let call = RuntimeCall::Did(update.into());
+
+    let (encoded_call, events) = submit_did_call(
+        call,
+        did_address,
+        chain_client,
+        payer,
+        authentication_signer,
+    )
+    .await?;
+
+    match events.find_first::<runtime::did::events::DidUpdated>()? {
+        Some(_) => {
+            log::info!("DID {:?} updated", did_address);
+            Ok(encoded_call)
+        }
+        None => Err(subxt::Error::Other(
+            "DidUpdated Event not found".to_string(),
+        )),
+    }
+}
+
+/// Creates the full DID of the authentication key with the given keys. The creation is signed
+/// by the authentication key and paid by the payer.
+pub async fn create_did(
+    keys: &DidKeys,
+    chain_client: &OnlineClient<KiltConfig>,
+    payer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+    authentication_signer: &PairSigner<KiltConfig, sp_core::sr25519::Pair>,
+) -> Result<AccountId32, subxt::Error> {
+    let did_address = AccountId32(keys.authentication_key.0);
+    let verification_key = |public: sp_core::sr25519::Public| {
+        DidVerificationKey::Sr25519(runtime_types::sp_core::sr25519::Public(public.0))
     };
 
-    let encoded_call = did_call.encode();
+    let details = DidCreationDetails {
+        did: did_address.clone(),
+        submitter: payer.account_id().to_owned().into(),
+        new_key_agreement_keys: BoundedBTreeSet(vec![DidEncryptionKey::X25519(
+            keys.key_agreement_key.0,
+        )]),
+        new_attestation_key: Some(verification_key(keys.attestation_key)),
+        new_delegation_key: Some(verification_key(keys.delegation_key)),
+        new_service_details: Vec::new(),
+        __subxt_unused_type_params: Default::default(),
+    };
 
-    let signature = calculate_signature(&did_call.encode(), &signer);
-    let final_tx = runtime::tx().did().submit_did_call(did_call, signature);
+    let signature = calculate_signature(&details.encode(), authentication_signer);
+    let tx = runtime::tx().did().create(details, signature);
     let events = chain_client
         .tx()
-        .sign_and_submit_then_watch_default(&final_tx, payer)
+        .sign_and_submit_then_watch_default(&tx, payer)
         .await?
         .wait_for_finalized_success()
         .await?;
 
-    let revoke_event = events.find_first::<runtime::attestation::events::AttestationRevoked>()?;
-
-    match revoke_event {
+    match events.find_first::<runtime::did::events::DidCreated>()? {
         Some(_) => {
-            log::info!("Attestation with root hash {:?} revoked", claim_hash);
-            Ok(encoded_call)
-        }
-        _ => {
-            log::info!(
-                "Attestation with root hash {:?} could not be revoked. Revoke Event not found",
-                claim_hash
-            );
-            Err(subxt::Error::Other("Created Event not found".to_string()))
+            log::info!("DID {:?} created", did_address);
+            Ok(did_address)
         }
+        None => Err(subxt::Error::Other(
+            "DidCreated Event not found".to_string(),
+        )),
     }
 }
@@ -44,13 +44,13 @@ use sp_core::Pair;
 
 type Blake2b256 = Blake2b<U32>;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, utoipa::ToSchema)]
 pub struct WellKnownDidConfig {
     pub context: String,
     pub linked_dids: Vec<LinkedDid>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, utoipa::ToSchema)]
 pub struct LinkedDid {
     pub context: Vec<String>,
     pub issuer: String,
@@ -65,7 +65,7 @@ pub struct LinkedDid {
     pub proof: Proof,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, utoipa::ToSchema)]
 pub struct CredentialSubject {
     pub id: String,
     pub origin: String,
@@ -73,7 +73,7 @@ pub struct CredentialSubject {
     pub root_hash: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, utoipa::ToSchema)]
 pub struct Proof {
     #[serde(rename = "type")]
     pub type_: String,
@@ -3,36 +3,48 @@ mod cli;
 mod configuration;
 mod database;
 mod error;
+mod evidence;
+mod health;
 mod kilt;
+mod metrics;
 mod routes;
+mod telemetry;
 mod utils;
 
 // external imports
 use actix_cors::Cors;
-use actix_web::{middleware::Logger, web, App, HttpServer};
-use actix_web_httpauth::middleware::HttpAuthentication;
+use actix_web::{
+    dev::{Service, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    web, App, HttpMessage, HttpServer,
+};
 use anyhow::Context;
 use clap::Parser;
 use sodiumoxide::crypto::box_::SecretKey;
 use sqlx::{Pool, Postgres};
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use subxt::{ext::sp_core::sr25519::Pair, tx::PairSigner, utils::AccountId32};
+use tracing_actix_web::{RequestId, TracingLogger};
 
 // internal imports
-use auth::jwt_validator;
 use cli::Cli;
-use configuration::{Configuration, SessionConfig};
-use kilt::{create_well_known_did_config, KiltConfig, WellKnownDidConfig};
-use routes::{
-    get_attestation_request_scope, get_challenge_scope, get_credential_scope, get_endpoint_scope,
-    well_known_did_config_handler,
-};
+use configuration::{CTypeConfig, Configuration, SessionConfig};
+use error::AppError;
+use evidence::EvidenceStore;
+use kilt::{create_well_known_did_config, Chain, KiltChain, KiltConfig, WellKnownDidConfig};
+use routes::{configure_api, metrics_handler};
 
 /// App State of the application. No need of read/write locks since we read only from the state.
 #[derive(Clone)]
 pub struct AppState {
     pub payer: Arc<PairSigner<KiltConfig, Pair>>,
     pub signer: Arc<PairSigner<KiltConfig, Pair>>,
+    pub authentication_signer: Arc<PairSigner<KiltConfig, Pair>>,
+    pub delegation_signer: Arc<PairSigner<KiltConfig, Pair>>,
     pub app_name: String,
     pub jwt_secret: String,
     pub db_executor: Arc<Pool<Postgres>>,
@@ -42,22 +54,45 @@ pub struct AppState {
     pub encryption_key: SecretKey,
     pub auth_url: String,
     pub endpoint: String,
+    pub ctypes: HashMap<String, CTypeConfig>,
+    pub evidence_store: Option<EvidenceStore>,
+    pub min_payer_balance: Option<u128>,
+    pub chain: Arc<dyn Chain>,
+}
+
+/// Errors of the extractors are wrapped, so that they are returned in the same format as the
+/// errors of the handlers.
+fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|e, _| AppError::ActixWeb(e.into()).into())
+}
+
+fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|e, _| AppError::ActixWeb(e.into()).into())
+}
+
+fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|e, _| AppError::ActixWeb(e.into()).into())
 }
 
 #[actix_web::main]
 async fn main() -> anyhow::Result<()> {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
-
     let cli = Cli::parse();
 
     let config = cli.get_config()?;
 
+    let _telemetry = telemetry::init(&config.logging).context("Initializing logging failed.")?;
+
+    if let Some(command) = &cli.command {
+        return command.run(&config).await;
+    }
+
     let attester_did = config.get_did().context("Did should be set")?;
 
     log::info!("Did: {}", attester_did);
 
     let port = config.port.clone();
     let front_end_path = config.front_end_path.clone();
+    let metrics_config = config.metrics.clone();
 
     let db_executor = database::connection::init(&config.database_url).await?;
 
@@ -77,6 +112,14 @@ async fn main() -> anyhow::Result<()> {
         .get_credential_signer()
         .context("Creating payer should not fail.")?;
 
+    let authentication_signer = config
+        .get_did_authentication_signer()
+        .context("Creating authentication signer should not fail.")?;
+
+    let delegation_signer = config
+        .get_delegation_signer()
+        .context("Creating delegation signer should not fail.")?;
+
     let payer = config
         .get_payer_signer()
         .context("Creating signer should not fail.")?;
@@ -88,41 +131,140 @@ async fn main() -> anyhow::Result<()> {
     let well_known_did_config = create_well_known_did_config(&config.well_known_did_config)
         .context("Creating well known did config should not fail.")?;
 
+    let evidence_store = config
+        .evidence
+        .as_ref()
+        .map(EvidenceStore::from_config)
+        .transpose()
+        .context("Creating evidence storage failed.")?;
+
+    let max_evidence_size = evidence_store
+        .as_ref()
+        .map_or(0, |store| store.max_file_size);
+
+    let min_payer_balance = config
+        .min_payer_balance
+        .as_ref()
+        .map(|balance| kilt::kilt_to_femto(balance).context("Invalid minimum payer balance."))
+        .transpose()?;
+
+    let payer = Arc::new(payer);
+    let signer = Arc::new(signer);
+
+    let chain = KiltChain::new(
+        config.endpoint.clone(),
+        attester_did.clone(),
+        payer.clone(),
+        signer.clone(),
+    );
+
     let app_state = AppState {
         session: config.session,
         jwt_secret: config.jwt_secret,
         app_name: config.app_name,
         well_known_did_config,
         db_executor: Arc::new(db_executor),
-        payer: Arc::new(payer),
-        signer: Arc::new(signer),
+        payer,
+        signer,
+        authentication_signer: Arc::new(authentication_signer),
+        delegation_signer: Arc::new(delegation_signer),
         attester_did,
         encryption_key,
         auth_url: config.auth_url,
         endpoint: config.endpoint,
+        ctypes: config.ctypes,
+        evidence_store,
+        min_payer_balance,
+        chain: Arc::new(chain),
     };
 
+    match health::run_self_check(&app_state).await {
+        Ok(report) => {
+            for warning in &report.warnings {
+                log::warn!("{}", warning);
+            }
+            if !report.is_healthy() {
+                anyhow::bail!("Self-check failed: {}", report.errors.join(", "));
+            }
+            log::info!("Self-check passed");
+        }
+        Err(e) => log::error!("Self-check could not be run: {}", e),
+    }
+
     log::info!("started server at port: {}", port);
 
-    HttpServer::new(move || {
+    let metrics_state = app_state.clone();
+
+    let server = HttpServer::new(move || {
         let cors = Cors::permissive();
-        let logger = Logger::default();
-        let auth = HttpAuthentication::bearer(jwt_validator);
 
         App::new()
-            .wrap(logger)
+            .wrap_fn(|req, srv| {
+                let started_at = Instant::now();
+                let method = req.method().to_string();
+                let request_id = req.extensions().get::<RequestId>().copied();
+                let http_req = req.request().clone();
+                let response = srv.call(req);
+                telemetry::with_request_id(request_id, async move {
+                    // Errors of inner middlewares, e.g. the authentication, are turned into
+                    // responses here, so that they contain the request ID as well.
+                    let mut res = match response.await {
+                        Ok(res) => res.map_into_boxed_body(),
+                        Err(e) => ServiceResponse::new(http_req, e.error_response()),
+                    };
+
+                    if let Some(request_id) = request_id {
+                        if let Ok(value) = HeaderValue::from_str(&request_id.to_string()) {
+                            res.headers_mut()
+                                .insert(HeaderName::from_static("x-request-id"), value);
+                        }
+                    }
+
+                    let route = res.request().match_pattern();
+                    metrics::observe_http_request(
+                        &method,
+                        route.as_deref().unwrap_or("unmatched"),
+                        res.status().as_u16(),
+                        started_at,
+                    );
+                    Ok::<_, actix_web::Error>(res)
+                })
+            })
             .wrap(cors)
+            .wrap(TracingLogger::default())
+            .app_data(json_config())
+            .app_data(path_config())
+            .app_data(query_config())
             .app_data(web::Data::new(app_state.clone()))
-            .service(get_attestation_request_scope().wrap(auth.clone()))
-            .service(get_challenge_scope().wrap(auth.clone()))
-            .service(get_credential_scope().wrap(auth.clone()))
-            .service(get_endpoint_scope())
-            .service(well_known_did_config_handler)
+            .configure(|config| configure_api(config, max_evidence_size))
             .service(actix_files::Files::new("/", &front_end_path).index_file("index.html"))
     })
     .bind(("0.0.0.0", port))?
-    .run()
-    .await?;
+    .run();
+
+    // Metrics are served on a separate port, so that they are not reachable through the API.
+    match metrics_config {
+        Some(metrics_config) => {
+            metrics::spawn_payer_balance_refresh(
+                metrics_state.clone(),
+                Duration::from_secs(metrics_config.balance_refresh_interval),
+            );
+
+            log::info!("started metrics server at port: {}", metrics_config.port);
+
+            let metrics_server = HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(metrics_state.clone()))
+                    .service(metrics_handler)
+            })
+            .workers(1)
+            .bind(("0.0.0.0", metrics_config.port))?
+            .run();
+
+            tokio::try_join!(server, metrics_server)?;
+        }
+        None => server.await?,
+    }
 
     Ok(())
 }
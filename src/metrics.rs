@@ -0,0 +1,202 @@
+//! Operational metrics which are exported in the Prometheus text format on `/metrics`.
+//! Request and extrinsic metrics are recorded when they happen, the gauges of the database and
+//! the attestation requests are refreshed on every scrape. The payer balance is read from the
+//! chain in a background interval, so that scrapes don't open connections to the chain node.
+
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use subxt::{utils::AccountId32, OnlineClient};
+
+use crate::{
+    database::querys::{attestation_request_state_counts, get_queue_depths},
+    kilt::{get_free_balance, KiltConfig},
+    AppState,
+};
+
+/// Time after which reading the payer balance is given up.
+const CHAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+const FEMTO_PER_KILT: f64 = 1e15;
+
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub extrinsics: IntCounterVec,
+    pub extrinsic_duration: HistogramVec,
+    pub db_pool_connections: IntGaugeVec,
+    pub payer_balance: prometheus::Gauge,
+    pub attestation_requests: IntGaugeVec,
+    pub queue_depth: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("attester".to_string()), None)?;
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Latency of HTTP requests in seconds",
+            ),
+            &["method", "route"],
+        )?;
+        let extrinsics = IntCounterVec::new(
+            Opts::new("extrinsics_total", "Number of submitted extrinsics"),
+            &["call", "outcome"],
+        )?;
+        let extrinsic_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "extrinsic_duration_seconds",
+                "Time until a submitted extrinsic is finalized in seconds",
+            )
+            .buckets(vec![6.0, 12.0, 18.0, 24.0, 36.0, 48.0, 60.0, 120.0, 300.0]),
+            &["call"],
+        )?;
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Connections of the database pool"),
+            &["state"],
+        )?;
+        let payer_balance =
+            prometheus::Gauge::new("payer_balance_kilt", "Free balance of the payer in KILT")?;
+        let attestation_requests = IntGaugeVec::new(
+            Opts::new("attestation_requests", "Number of attestation requests"),
+            &["state", "ctype_hash"],
+        )?;
+        let queue_depth = IntGaugeVec::new(
+            Opts::new("queue_depth", "Number of pending background jobs"),
+            &["queue"],
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(extrinsics.clone()))?;
+        registry.register(Box::new(extrinsic_duration.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(payer_balance.clone()))?;
+        registry.register(Box::new(attestation_requests.clone()))?;
+        registry.register(Box::new(queue_depth.clone()))?;
+
+        Ok(Metrics {
+            registry,
+            http_requests,
+            http_request_duration,
+            extrinsics,
+            extrinsic_duration,
+            db_pool_connections,
+            payer_balance,
+            attestation_requests,
+            queue_depth,
+        })
+    }
+
+    pub fn encode(&self) -> prometheus::Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("Metrics should be valid"))
+}
+
+pub fn observe_http_request(method: &str, route: &str, status: u16, started_at: Instant) {
+    let metrics = metrics();
+    metrics
+        .http_requests
+        .with_label_values(&[method, route, &status.to_string()])
+        .inc();
+    metrics
+        .http_request_duration
+        .with_label_values(&[method, route])
+        .observe(started_at.elapsed().as_secs_f64());
+}
+
+pub fn observe_extrinsic<T, E>(call: &str, started_at: Instant, result: &Result<T, E>) {
+    let metrics = metrics();
+    let outcome = if result.is_ok() { "success" } else { "failure" };
+    metrics.extrinsics.with_label_values(&[call, outcome]).inc();
+    metrics
+        .extrinsic_duration
+        .with_label_values(&[call])
+        .observe(started_at.elapsed().as_secs_f64());
+}
+
+/// Updates the gauges which are read from the database.
+pub async fn refresh(state: &AppState) {
+    let metrics = metrics();
+    let pool = &state.db_executor;
+
+    let idle = pool.num_idle() as i64;
+    metrics
+        .db_pool_connections
+        .with_label_values(&["idle"])
+        .set(idle);
+    metrics
+        .db_pool_connections
+        .with_label_values(&["active"])
+        .set(pool.size() as i64 - idle);
+
+    match attestation_request_state_counts(pool).await {
+        Ok(counts) => {
+            metrics.attestation_requests.reset();
+            for count in counts {
+                metrics
+                    .attestation_requests
+                    .with_label_values(&[&count.state, &count.ctype_hash])
+                    .set(count.count);
+            }
+        }
+        Err(e) => log::error!("Counting attestation requests failed: {}", e),
+    }
+
+    match get_queue_depths(pool).await {
+        Ok((in_flight, undelivered)) => {
+            metrics
+                .queue_depth
+                .with_label_values(&["in_flight_transactions"])
+                .set(in_flight);
+            metrics
+                .queue_depth
+                .with_label_values(&["undelivered_attestations"])
+                .set(undelivered);
+        }
+        Err(e) => log::error!("Reading queue depths failed: {}", e),
+    }
+}
+
+async fn refresh_payer_balance(state: &AppState) {
+    let payer = AccountId32(state.payer.account_id().clone().into());
+    let balance = async {
+        let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+        get_free_balance(&payer, &chain_client).await
+    };
+    match tokio::time::timeout(CHAIN_TIMEOUT, balance).await {
+        Ok(Ok(balance)) => metrics().payer_balance.set(balance as f64 / FEMTO_PER_KILT),
+        Ok(Err(e)) => log::error!("Reading payer balance failed: {}", e),
+        Err(_) => log::error!("Reading payer balance timed out"),
+    }
+}
+
+/// Reads the payer balance in the given interval for as long as the server runs.
+pub fn spawn_payer_balance_refresh(state: AppState, interval: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            refresh_payer_balance(&state).await;
+        }
+    });
+}
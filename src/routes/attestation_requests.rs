@@ -3,28 +3,52 @@ use actix_web::{
     web::{self, ReqData},
     HttpResponse, Scope,
 };
+use attester_api::routes;
 
-use subxt::{ext::sp_core::H256, OnlineClient};
+use subxt::ext::sp_core::H256;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use crate::{
     auth::User,
     database::{
-        dto::{Credential, Pagination, Query},
+        dto::{
+            ApprovalStatus, Assignment, Attestation, AttestationKPIs, AttestationResponse,
+            AttestationState, AttestationTransition, Comment, Credential, ErrorResponse, KpiFilter,
+            NewAssignment, NewComment, Pagination, Query, Rejection, Review, ReviewDecision,
+            Revision,
+        },
         querys::{
-            approve_attestation_request, attestation_requests_kpis, can_approve_attestation_tx,
-            can_revoke_attestation, delete_attestation_request, get_attestation_request_by_id,
-            get_attestation_requests, get_attestations_count, insert_attestation_request,
-            mark_attestation_approve, mark_attestation_request_in_flight,
-            record_attestation_request_failed, revoke_attestation_request,
+            approve_attestation_request, assign_reviewer, attestation_requests_kpis,
+            can_approve_attestation_tx, can_revoke_attestation, count_approvals,
+            delete_attestation_request, get_assignments, get_attestation_request_by_id,
+            get_attestation_request_revisions, get_attestation_requests, get_attestations_count,
+            get_comments, get_reviews, get_revocation_requests, insert_attestation_request,
+            insert_comment, insert_review, mark_attestation_approve,
+            mark_attestation_request_in_flight, queue_attestation_message,
+            record_attestation_request_failed, reject_attestation_request,
+            request_attestation_changes, request_attestation_revocation,
+            revoke_attestation_request, update_attestation_request_credential,
         },
     },
     error::AppError,
-    kilt::KiltConfig,
-    utils::{is_user_admin, is_user_allowed_to_see_data, is_user_allowed_to_update_data},
+    evidence::purge_evidences,
+    utils::{
+        get_ctype_config, is_user_admin, is_user_allowed_to_review, is_user_allowed_to_see_data,
+        is_user_allowed_to_update_data,
+    },
     AppState,
 };
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The attestation request", body = AttestationResponse),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 404, description = "The resource does not exist", body = ErrorResponse),
+    )
+)]
 #[get("/{attestation_request_id}")]
 async fn get_attestation(
     attestation_id: web::Path<Uuid>,
@@ -36,12 +60,21 @@ async fn get_attestation(
     if is_user_allowed {
         Ok(HttpResponse::Ok().json(serde_json::to_value(&attestation)?))
     } else {
-        Err(actix_web::error::ErrorUnauthorized(
-            "User is not allowed to see data",
+        Err(AppError::Forbidden(
+            "User is not allowed to see this attestation request",
         ))?
     }
 }
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    params(Query),
+    responses(
+        (status = 200, description = "The attestation requests of the page", body = [AttestationResponse], headers(("Content-Range" = String, description = "Total number of attestation requests"))),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
 #[get("")]
 async fn get_attestations(
     state: web::Data<AppState>,
@@ -62,12 +95,21 @@ async fn get_attestations(
             .insert_header(("Content-Range", content_range))
             .json(response))
     } else {
-        Err(actix_web::error::ErrorUnauthorized(
-            "User is not allowed to see data",
+        Err(AppError::Forbidden(
+            "User is not allowed to see attestation requests",
         ))?
     }
 }
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The attestation request is deleted", body = String),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 404, description = "The resource does not exist", body = ErrorResponse),
+    )
+)]
 #[delete("/{attestation_request_id}")]
 async fn delete_attestation(
     attestation_id: web::Path<Uuid>,
@@ -78,16 +120,34 @@ async fn delete_attestation(
         is_user_allowed_to_update_data(&user, &attestation_id, &state.db_executor).await?;
 
     if !is_user_allowed {
-        Err(actix_web::error::ErrorUnauthorized(
-            "User is not allowed to see data",
+        Err(AppError::Forbidden(
+            "User is not allowed to delete this attestation request",
+        ))?
+    }
+
+    // approved attestations are anchored on chain and have to be revoked before they can be deleted.
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+    if attestation.status == AttestationState::Approved {
+        Err(AppError::Conflict(
+            "Approved attestations have to be revoked before deletion",
         ))?
     }
 
     delete_attestation_request(&attestation_id, &state.db_executor).await?;
+    purge_evidences(&attestation_id, &state).await;
     log::info!("Attestation with id {:?} is deleted", attestation_id);
     Ok(HttpResponse::Ok().json("ok"))
 }
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    request_body = Credential,
+    responses(
+        (status = 200, description = "The created attestation request", body = AttestationResponse),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+    )
+)]
 #[post("")]
 async fn post_attestation(
     claim_request: web::Json<Credential>,
@@ -98,6 +158,15 @@ async fn post_attestation(
     Ok(HttpResponse::Ok().json(attestation))
 }
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The approvals of the attestation request", body = ApprovalStatus),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
 #[put("/{attestation_request_id}/approve")]
 async fn approve_attestation(
     attestation_id: web::Path<Uuid>,
@@ -106,73 +175,221 @@ async fn approve_attestation(
 ) -> Result<HttpResponse, AppError> {
     // check role
     if !is_user_admin(&user) {
-        Err(actix_web::error::ErrorUnauthorized(
-            "User is not allowed to see data",
+        Err(AppError::Forbidden(
+            "Only admins can approve attestation requests",
         ))?
     }
 
     // start session for db
     let mut tx = state.db_executor.begin().await?;
     let attestation = can_approve_attestation_tx(&attestation_id, &mut tx).await?;
+    let previous_state = attestation.status;
+
+    if !is_user_allowed_to_review(&user, &attestation, &state.db_executor).await? {
+        Err(AppError::Forbidden(
+            "User is not allowed to review this attestation",
+        ))?
+    }
+
     let credential: Credential = serde_json::from_value(attestation.credential)?;
     let ctype_hash = hex::decode(credential.claim.ctype_hash.trim_start_matches("0x").trim())?;
     let claim_hash = hex::decode(credential.root_hash.trim_start_matches("0x").trim())?;
     if claim_hash.len() != 32 || ctype_hash.len() != 32 {
-        Err(actix_web::error::ErrorBadRequest(
+        Err(AppError::BadRequest(
             "Claim hash or ctype hash have a wrong format",
         ))?
     }
 
-    let payer = state.payer.clone();
-    let did = state.attester_did.clone();
-    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
-    let signer = state.signer.clone();
+    let approval_status = record_approval(
+        &attestation_id,
+        &attestation.ctype_hash,
+        &user,
+        &state,
+        &mut tx,
+    )
+    .await?;
+
+    if approval_status.approvals < approval_status.required_approvals {
+        tx.commit().await?;
+        log::info!(
+            "Attestation with id {:?} has {} of {} required approvals",
+            attestation_id,
+            approval_status.approvals,
+            approval_status.required_approvals
+        );
+        return Ok(HttpResponse::Ok().json(approval_status));
+    }
+
+    let ctype_config = get_ctype_config(&state.ctypes, &attestation.ctype_hash);
+
+    crate::kilt::verify_legitimations(
+        &credential,
+        &ctype_config.legitimations,
+        state.chain.as_ref(),
+    )
+    .await?;
+
+    let delegation_id = crate::kilt::get_delegation_id(
+        ctype_config.delegation_id.as_deref(),
+        credential.delegation_id.as_deref(),
+    )?;
+    if let Some(delegation_id) = delegation_id {
+        crate::kilt::verify_delegation_node(
+            delegation_id,
+            &state.attester_did,
+            H256::from_slice(&ctype_hash),
+            state.chain.as_ref(),
+        )
+        .await?;
+    }
+
+    // The claimer receives the attestation once a session with the wallet is opened.
+    let attestation_message = serde_json::to_value(Attestation {
+        claim_hash: credential.root_hash,
+        ctype_hash: credential.claim.ctype_hash,
+        owner: crate::kilt::get_did_uri(&state.attester_did),
+        delegation_id: credential.delegation_id,
+        revoked: false,
+    })?;
 
     log::info!(
         "Attestation with id {:?} is getting approved",
         attestation_id
     );
 
+    mark_in_flight(&attestation_id, AttestationTransition::Approve, &mut tx).await?;
+    tx.commit().await?;
+
+    let span = tracing::info_span!("approve_attestation", attestation_id = %*attestation_id);
+
     // send tx async
-    tokio::spawn(async move {
-        let _ = mark_attestation_request_in_flight(&attestation_id, &state.db_executor).await;
+    tokio::spawn(
+        async move {
+            let result_create_claim = state
+                .chain
+                .create_claim(
+                    H256::from_slice(&claim_hash),
+                    H256::from_slice(&ctype_hash),
+                    delegation_id,
+                )
+                .await;
+
+            let mut tx = match state.db_executor.begin().await {
+                Ok(tx) => tx,
+                Err(err) => {
+                    log::error!(
+                        "Error: Something went wrong with db_executor.begin: {:?}",
+                        err
+                    );
+                    return;
+                }
+            };
+
+            if let Err(err) = result_create_claim {
+                log::error!("Error: Something went wrong with create_claim: {:?}", err,);
+                let _ = record_attestation_request_failed(&attestation_id, previous_state, &mut tx)
+                    .await;
+                let _ = tx.commit().await;
+                return;
+            }
 
-        let result_create_claim = crate::kilt::create_claim(
-            H256::from_slice(&claim_hash),
-            H256::from_slice(&ctype_hash),
-            &did,
-            &chain_client,
-            &payer,
-            &signer,
-        )
-        .await;
+            if let Err(err) = approve_attestation_request(&attestation_id, &mut tx).await {
+                log::error!(
+                    "Error: Something went wrong with approve_attestation_request_tx: {:?}",
+                    err
+                );
+                return;
+            }
 
-        if let Err(err) = result_create_claim {
-            log::error!("Error: Something went wrong with create_claim: {:?}", err,);
-            let _ = record_attestation_request_failed(&attestation_id, &mut tx).await;
-            let _ = tx.commit().await;
-            return;
-        }
+            if let Err(err) =
+                queue_attestation_message(&attestation_id, &attestation_message, &mut tx).await
+            {
+                log::error!(
+                    "Error: Something went wrong with queue_attestation_message: {:?}",
+                    err
+                );
+                return;
+            }
 
-        if let Err(err) = approve_attestation_request(&attestation_id, &mut tx).await {
-            log::error!(
-                "Error: Something went wrong with approve_attestation_request_tx: {:?}",
-                err
-            );
-            return;
-        }
+            if let Err(err) = tx.commit().await {
+                log::error!("Error: Something went wrong with tx.commit: {:?}", err);
+                return;
+            }
 
-        if let Err(err) = tx.commit().await {
-            log::error!("Error: Something went wrong with tx.commit: {:?}", err);
-            return;
+            log::info!("Attestation with id {:?} is approved", attestation_id);
+            purge_evidences(&attestation_id, &state).await;
         }
+        .instrument(span),
+    );
 
-        log::info!("Attestation with id {:?} is approved", attestation_id);
-    });
+    Ok(HttpResponse::Ok().json(approval_status))
+}
 
-    Ok(HttpResponse::Ok().json("ok"))
+/// Marks the transaction of the attestation request as in flight, unless another one is in flight
+/// already. Must be called right before the transaction is submitted, and committed before the
+/// row lock of the request is released.
+async fn mark_in_flight(
+    attestation_id: &Uuid,
+    transition: AttestationTransition,
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+) -> Result<(), AppError> {
+    let result = mark_attestation_request_in_flight(attestation_id, &mut **tx).await?;
+    if result.rows_affected() == 0 {
+        Err(AppError::InvalidTransition {
+            state: AttestationState::InFlight,
+            transition,
+        })?
+    }
+    Ok(())
 }
 
+/// Records the approval of the reviewer and returns how many distinct approvals the request has.
+/// A reviewer can approve a revision of the credential only once, but can retry the submission
+/// once all required approvals are collected.
+async fn record_approval(
+    attestation_id: &Uuid,
+    ctype_hash: &str,
+    user: &User,
+    state: &AppState,
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+) -> Result<ApprovalStatus, AppError> {
+    let already_approved = count_approvals(attestation_id, Some(&user.id), tx).await? > 0;
+    if !already_approved {
+        insert_review(
+            attestation_id,
+            &user.id,
+            ReviewDecision::Approved,
+            &mut **tx,
+        )
+        .await?;
+    }
+
+    let approvals = count_approvals(attestation_id, None, tx).await?;
+    let required_approvals = get_ctype_config(&state.ctypes, ctype_hash)
+        .required_approvals
+        .into();
+
+    if already_approved && approvals < required_approvals {
+        Err(AppError::Conflict(
+            "Attestation is already approved by this reviewer",
+        ))?
+    }
+
+    Ok(ApprovalStatus {
+        approvals,
+        required_approvals,
+    })
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The approvals of the attestation request", body = ApprovalStatus),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
 #[put("/{attestation_request_id}/mark_approve")]
 async fn mark_approve_attestation_request(
     attestation_id: web::Path<Uuid>,
@@ -181,16 +398,388 @@ async fn mark_approve_attestation_request(
 ) -> Result<HttpResponse, AppError> {
     // check role
     if !is_user_admin(&user) {
-        Err(actix_web::error::ErrorUnauthorized(
-            "User is not allowed to see data",
+        Err(AppError::Forbidden(
+            "Only admins can approve attestation requests",
         ))?
     }
 
-    mark_attestation_approve(&state.db_executor, &attestation_id).await?;
+    let mut tx = state.db_executor.begin().await?;
+    let attestation = can_approve_attestation_tx(&attestation_id, &mut tx).await?;
+
+    if !is_user_allowed_to_review(&user, &attestation, &state.db_executor).await? {
+        Err(AppError::Forbidden(
+            "User is not allowed to review this attestation",
+        ))?
+    }
+
+    let approval_status = record_approval(
+        &attestation_id,
+        &attestation.ctype_hash,
+        &user,
+        &state,
+        &mut tx,
+    )
+    .await?;
+
+    let is_approved = approval_status.approvals >= approval_status.required_approvals;
+    if is_approved {
+        mark_attestation_approve(&mut tx, &attestation_id).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(HttpResponse::Ok().json(approval_status))
+}
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    request_body = Rejection,
+    responses(
+        (status = 200, description = "The attestation request is rejected", body = String),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
+#[put("/{attestation_request_id}/reject")]
+async fn reject_attestation(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+    rejection: web::Json<Rejection>,
+) -> Result<HttpResponse, AppError> {
+    // check role
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden(
+            "Only admins can reject attestation requests",
+        ))?
+    }
+
+    let reason = rejection.reason.trim();
+    if reason.is_empty() {
+        Err(AppError::BadRequest(
+            "A reason for the rejection is required",
+        ))?
+    }
+
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+    if !is_user_allowed_to_review(&user, &attestation, &state.db_executor).await? {
+        Err(AppError::Forbidden(
+            "User is not allowed to review this attestation",
+        ))?
+    }
+
+    attestation.check_transition(AttestationTransition::Reject)?;
+
+    let mut tx = state.db_executor.begin().await?;
+    reject_attestation_request(&attestation_id, &user.id, reason, &mut *tx).await?;
+    insert_review(
+        &attestation_id,
+        &user.id,
+        ReviewDecision::Rejected,
+        &mut *tx,
+    )
+    .await?;
+    tx.commit().await?;
+
+    purge_evidences(&attestation_id, &state).await;
+    log::info!(
+        "Attestation with id {:?} is rejected by {}",
+        attestation_id,
+        user.id
+    );
     Ok(HttpResponse::Ok().json("ok"))
 }
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    request_body = NewComment,
+    responses(
+        (status = 200, description = "Changes of the credential are requested", body = String),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
+#[put("/{attestation_request_id}/request_changes")]
+async fn request_changes(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+    comment: web::Json<NewComment>,
+) -> Result<HttpResponse, AppError> {
+    // check role
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can request changes"))?
+    }
+
+    let message = comment.message.trim();
+    if message.is_empty() {
+        Err(AppError::BadRequest(
+            "A message describing the requested changes is required",
+        ))?
+    }
+
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+    if !is_user_allowed_to_review(&user, &attestation, &state.db_executor).await? {
+        Err(AppError::Forbidden(
+            "User is not allowed to review this attestation",
+        ))?
+    }
+
+    attestation.check_transition(AttestationTransition::RequestChanges)?;
+
+    let mut tx = state.db_executor.begin().await?;
+    request_attestation_changes(&attestation_id, &mut tx).await?;
+    insert_comment(&attestation_id, &user.id, message, &mut *tx).await?;
+    insert_review(
+        &attestation_id,
+        &user.id,
+        ReviewDecision::ChangesRequested,
+        &mut *tx,
+    )
+    .await?;
+    tx.commit().await?;
+
+    log::info!(
+        "Changes for attestation with id {:?} are requested by {}",
+        attestation_id,
+        user.id
+    );
+    Ok(HttpResponse::Ok().json("ok"))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    request_body = Credential,
+    responses(
+        (status = 200, description = "The updated attestation request", body = AttestationResponse),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
+#[put("/{attestation_request_id}/credential")]
+async fn update_credential(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+    credential: web::Json<Credential>,
+) -> Result<HttpResponse, AppError> {
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+
+    // only the claimer can submit a new version of the credential.
+    if attestation.claimer != user.id {
+        Err(AppError::Forbidden(
+            "Only the claimer can update the attestation request",
+        ))?
+    }
+
+    if credential.claim.owner != attestation.claimer
+        || credential.claim.ctype_hash != attestation.ctype_hash
+    {
+        Err(AppError::BadRequest(
+            "Owner and ctype of the credential can not be changed",
+        ))?
+    }
+
+    crate::kilt::verify_credential_hashes(&credential)?;
+
+    let mut tx = state.db_executor.begin().await?;
+    let updated_attestation =
+        update_attestation_request_credential(&attestation_id, &credential, &mut tx).await?;
+    tx.commit().await?;
+
+    log::info!(
+        "Credential of attestation with id {:?} is updated",
+        attestation_id
+    );
+    Ok(HttpResponse::Ok().json(updated_attestation))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The previous versions of the credential", body = [Revision]),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("/{attestation_request_id}/revisions")]
+async fn get_revisions(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let is_user_allowed =
+        is_user_allowed_to_update_data(&user, &attestation_id, &state.db_executor).await?;
+
+    if !is_user_allowed {
+        Err(AppError::Forbidden(
+            "User is not allowed to see the revisions of this attestation request",
+        ))?
+    }
+
+    let revisions = get_attestation_request_revisions(&attestation_id, &state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(serde_json::to_value(&revisions)?))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The comments of the attestation request", body = [Comment]),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("/{attestation_request_id}/comments")]
+async fn get_attestation_comments(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let is_user_allowed =
+        is_user_allowed_to_update_data(&user, &attestation_id, &state.db_executor).await?;
+
+    if !is_user_allowed {
+        Err(AppError::Forbidden(
+            "User is not allowed to see the comments of this attestation request",
+        ))?
+    }
+
+    let comments = get_comments(&attestation_id, &state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(serde_json::to_value(&comments)?))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    request_body = NewComment,
+    responses(
+        (status = 200, description = "The created comment", body = Comment),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[post("/{attestation_request_id}/comments")]
+async fn post_attestation_comment(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+    comment: web::Json<NewComment>,
+) -> Result<HttpResponse, AppError> {
+    let is_user_allowed =
+        is_user_allowed_to_update_data(&user, &attestation_id, &state.db_executor).await?;
+
+    if !is_user_allowed {
+        Err(AppError::Forbidden(
+            "User is not allowed to comment on this attestation request",
+        ))?
+    }
+
+    let message = comment.message.trim();
+    if message.is_empty() {
+        Err(AppError::BadRequest("Message is empty"))?
+    }
+
+    let comment = insert_comment(&attestation_id, &user.id, message, &*state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(comment))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    request_body = NewAssignment,
+    responses(
+        (status = 200, description = "The assignment of the reviewer", body = Assignment),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[put("/{attestation_request_id}/reviewers")]
+async fn assign_attestation_reviewer(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+    assignment: web::Json<NewAssignment>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can assign reviewers"))?
+    }
+
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+    if attestation.claimer == assignment.reviewer {
+        Err(AppError::BadRequest(
+            "The claimer can not review the own attestation",
+        ))?
+    }
+
+    let assignment = assign_reviewer(
+        &attestation_id,
+        &assignment.reviewer,
+        &user.id,
+        &state.db_executor,
+    )
+    .await?;
+    log::info!(
+        "Reviewer {} is assigned to attestation with id {:?}",
+        assignment.reviewer,
+        attestation_id
+    );
+    Ok(HttpResponse::Ok().json(assignment))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The assigned reviewers", body = [Assignment]),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("/{attestation_request_id}/reviewers")]
+async fn get_attestation_reviewers(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can see the reviewers"))?
+    }
+
+    let assignments = get_assignments(&attestation_id, &state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(serde_json::to_value(&assignments)?))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The reviews of the attestation request", body = [Review]),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("/{attestation_request_id}/reviews")]
+async fn get_attestation_reviews(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can see the reviews"))?
+    }
+
+    let reviews = get_reviews(&attestation_id, &state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(serde_json::to_value(&reviews)?))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The attestation is revoked", body = String),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
 #[put("/{attestation_request_id}/revoke")]
 async fn revoke_attestation(
     attestation_id: web::Path<Uuid>,
@@ -199,86 +788,176 @@ async fn revoke_attestation(
 ) -> Result<HttpResponse, AppError> {
     // is user allowed
     if !is_user_admin(&user) {
-        Err(actix_web::error::ErrorUnauthorized(
-            "User is not allowed to see data",
-        ))?
+        Err(AppError::Forbidden("Only admins can revoke attestations"))?
     }
 
     // start db tx
     let mut tx = state.db_executor.begin().await?;
     let attestation = can_revoke_attestation(&attestation_id, &mut tx).await?;
+    let previous_state = attestation.status;
     let credential: Credential = serde_json::from_value(attestation.credential)?;
     let claim_hash = hex::decode(credential.root_hash.trim_start_matches("0x").trim())?;
     if claim_hash.len() != 32 {
-        Err(actix_web::error::ErrorBadRequest(
-            "Claim hash has a wrong format",
-        ))?
+        Err(AppError::BadRequest("Claim hash has a wrong format"))?
     }
 
-    let payer = state.payer.clone();
-    let did = state.attester_did.clone();
-    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
-    let signer = state.signer.clone();
-
     log::info!(
         "Attestation with id {:?} is getting revoked",
         attestation_id
     );
 
+    mark_in_flight(&attestation_id, AttestationTransition::Revoke, &mut tx).await?;
+    tx.commit().await?;
+
+    let span = tracing::info_span!("revoke_attestation", attestation_id = %*attestation_id);
+
     // revoke attestation async in db.
-    tokio::spawn(async move {
-        {
-            let _ = mark_attestation_request_in_flight(&attestation_id, &state.db_executor).await;
-
-            if let Err(err) = crate::kilt::revoke_claim(
-                H256::from_slice(&claim_hash),
-                &did,
-                &chain_client,
-                &payer,
-                &signer,
-            )
-            .await
+    tokio::spawn(
+        async move {
             {
-                log::error!("Error: Something went wrong with revoke_claim: {:?}", err);
-                let _ = record_attestation_request_failed(&attestation_id, &mut tx).await;
-                let _ = tx.commit().await;
-                return;
+                let result_revoke_claim = state
+                    .chain
+                    .revoke_claim(H256::from_slice(&claim_hash))
+                    .await;
+
+                let mut tx = match state.db_executor.begin().await {
+                    Ok(tx) => tx,
+                    Err(err) => {
+                        log::error!("Something went wrong with db_executor.begin: {:?}", err);
+                        return;
+                    }
+                };
+
+                if let Err(err) = result_revoke_claim {
+                    log::error!("Error: Something went wrong with revoke_claim: {:?}", err);
+                    let _ =
+                        record_attestation_request_failed(&attestation_id, previous_state, &mut tx)
+                            .await;
+                    let _ = tx.commit().await;
+                    return;
+                }
+
+                if let Err(err) = revoke_attestation_request(&attestation_id, &mut tx).await {
+                    log::error!(
+                        "Something went wrong with revoke_attestation_request: {:?}",
+                        err
+                    );
+                    return;
+                }
+
+                if let Err(err) = tx.commit().await {
+                    log::error!("Something went wrong with tx.commit: {:?}", err);
+                    return;
+                }
+
+                log::info!("Attestation with id {:?} is revoked", attestation_id);
             }
+        }
+        .instrument(span),
+    );
 
-            if let Err(err) = revoke_attestation_request(&attestation_id, &mut tx).await {
-                log::error!(
-                    "Something went wrong with revoke_attestation_request: {:?}",
-                    err
-                );
-                return;
-            }
+    Ok(HttpResponse::Ok().json("ok"))
+}
 
-            if let Err(err) = tx.commit().await {
-                log::error!("Something went wrong with tx.commit: {:?}", err);
-                return;
-            }
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The revocation is requested", body = String),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
+#[put("/{attestation_request_id}/request_revocation")]
+async fn request_revocation(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
 
-            log::info!("Attestation with id {:?} is revoked", attestation_id);
-        }
-    });
+    // only the claimer can request the revocation, admins revoke directly.
+    if attestation.claimer != user.id {
+        Err(AppError::Forbidden(
+            "Only the claimer can request the revocation",
+        ))?
+    }
 
+    let mut tx = state.db_executor.begin().await?;
+    request_attestation_revocation(&attestation_id, &mut tx).await?;
+    tx.commit().await?;
+    log::info!(
+        "Revocation for attestation with id {:?} is requested",
+        attestation_id
+    );
     Ok(HttpResponse::Ok().json("ok"))
 }
 
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    responses(
+        (status = 200, description = "The attestation requests with a requested revocation", body = [AttestationResponse]),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("/revocation_requests")]
+async fn get_attestation_revocation_requests(
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden(
+            "Only admins can see revocation requests",
+        ))?
+    }
+
+    let revocation_requests = get_revocation_requests(&state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(serde_json::to_value(&revocation_requests)?))
+}
+
+#[utoipa::path(
+    context_path = routes::ATTESTATION_REQUEST,
+    tag = "attestation_request",
+    params(KpiFilter),
+    responses(
+        (status = 200, description = "The KPIs of the attestation requests", body = AttestationKPIs),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
 #[get("/metric/kpis")]
-async fn get_attestation_kpis(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
-    let kpis = attestation_requests_kpis(&state.db_executor).await?;
+async fn get_attestation_kpis(
+    filter: web::Query<KpiFilter>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can see the statistics"))?
+    }
+
+    let kpis = attestation_requests_kpis(&filter, &state.db_executor).await?;
     Ok(HttpResponse::Ok().json(serde_json::to_value(kpis)?))
 }
 
 pub fn get_attestation_request_scope() -> Scope {
-    web::scope("/api/v1/attestation_request")
+    web::scope(routes::ATTESTATION_REQUEST)
         .service(approve_attestation)
+        .service(get_attestation_revocation_requests)
         .service(get_attestation)
         .service(get_attestations)
         .service(post_attestation)
         .service(delete_attestation)
         .service(revoke_attestation)
+        .service(reject_attestation)
+        .service(request_changes)
+        .service(update_credential)
+        .service(get_revisions)
+        .service(get_attestation_comments)
+        .service(post_attestation_comment)
+        .service(assign_attestation_reviewer)
+        .service(get_attestation_reviewers)
+        .service(get_attestation_reviews)
         .service(get_attestation_kpis)
         .service(mark_approve_attestation_request)
+        .service(request_revocation)
 }
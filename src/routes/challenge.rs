@@ -1,16 +1,24 @@
 use actix_web::{get, post, web, HttpResponse, Scope};
+use attester_api::routes;
 use sodiumoxide::crypto::box_;
 use uuid::Uuid;
 
 use crate::{
     database::{
-        dto::{ChallengeData, ChallengeResponse},
+        dto::{ChallengeData, ChallengeResponse, ErrorResponse},
         querys::{generate_new_session, update_session},
     },
     error::AppError,
     AppState,
 };
 
+#[utoipa::path(
+    context_path = routes::CHALLENGE,
+    tag = "challenge",
+    responses(
+        (status = 200, description = "A new challenge for the wallet session", body = ChallengeData),
+    )
+)]
 #[get("")]
 async fn challenge_handler(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
     let app_name = state.app_name.clone();
@@ -25,6 +33,15 @@ async fn challenge_handler(state: web::Data<AppState>) -> Result<HttpResponse, A
     Ok(HttpResponse::Ok().json(challenge_data))
 }
 
+#[utoipa::path(
+    context_path = routes::CHALLENGE,
+    tag = "challenge",
+    request_body = ChallengeResponse,
+    responses(
+        (status = 200, description = "The ID of the wallet session", body = Uuid),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+    )
+)]
 #[post("")]
 async fn challenge_response_handler(
     state: web::Data<AppState>,
@@ -50,7 +67,7 @@ async fn challenge_response_handler(
 }
 
 pub fn get_challenge_scope() -> Scope {
-    web::scope("/api/v1/challenge")
+    web::scope(routes::CHALLENGE)
         .service(challenge_handler)
         .service(challenge_response_handler)
 }
@@ -1,24 +1,50 @@
-use actix_web::{post, web, HttpResponse, Scope};
-use sodiumoxide::crypto::box_;
+use actix_web::{
+    get, post,
+    web::{self, ReqData},
+    HttpResponse, Scope,
+};
+use attester_api::routes;
+use serde::Serialize;
+use sodiumoxide::{crypto::box_, randombytes};
 use sp_core::H256;
-use subxt::OnlineClient;
+use subxt::utils::AccountId32;
 use uuid::Uuid;
 
 use crate::{
+    auth::User,
     database::{
         dto::{
-            Credential, EncryptedMessage, Message, MessageBody, RequestAttestationMessageContent,
-            SubmitTermsMessageContent,
+            Attestation, AttestationState, CTypeRequest, ConfirmPaymentMessageContent, Credential,
+            EncryptedMessage, ErrorMessageContent, ErrorResponse, Message, MessageContent,
+            QuoteAgreement, RejectAttestationMessageContent, RejectMessageContent,
+            RequestAttestationMessageContent, RequestCredentialMessageContent,
+            RequestPaymentMessageContent, SubmitAttestationMessageContent,
+            SubmitCredentialMessageContent, SubmitTermsMessageContent, VerificationRequest,
         },
         querys::{
-            approve_attestation_request, get_attestation_request_by_id, get_session, remove_session,
+            approve_attestation_request, can_attest_marked_approved_tx,
+            complete_verification_request, get_attestation_message, get_attestation_request_by_id,
+            get_open_verification_request, get_quote_agreement, get_session,
+            get_verification_request, insert_verification_request,
+            mark_attestation_message_delivered, mark_attestation_request_in_flight,
+            mark_quote_paid, record_attestation_request_failed, remove_session,
+            upsert_quote_agreement,
         },
     },
     error::AppError,
-    kilt::KiltConfig,
+    evidence::purge_evidences,
+    utils::get_ctype_config,
     AppState,
 };
 
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    responses(
+        (status = 200, description = "The encrypted submit-terms message", body = EncryptedMessage),
+        (status = 404, description = "The resource does not exist", body = ErrorResponse),
+    )
+)]
 #[post("/terms/{session_id}/{attestation_id}")]
 async fn send_terms(
     state: web::Data<AppState>,
@@ -34,9 +60,7 @@ async fn send_terms(
             "Found session without encryption key uri in send terms: {:?}",
             session_id
         );
-        Err(actix_web::error::ErrorBadRequest(
-            "Session set up not completed",
-        ))?
+        Err(AppError::Conflict("Session set up not completed"))?
     }
 
     let sender_key_uri = session.encryption_key_uri.unwrap();
@@ -48,52 +72,55 @@ async fn send_terms(
 
     let credential: Credential = serde_json::from_value(attestation.credential)?;
 
-    let encryption_key_uri = state.session.key_uri.clone();
-
-    let sender = encryption_key_uri
-        .split('#')
-        .collect::<Vec<&str>>()
-        .first()
-        .ok_or_else(|| AppError::Attestation("Invalid Key URI for sender"))?
-        .to_owned();
-
     let mut claim = credential.claim;
 
     claim.ctype_hash = ctype_hash.clone();
 
+    let ctype_config = get_ctype_config(&state.ctypes, &ctype_hash);
+
+    let quote = match ctype_config.quote {
+        Some(quote_config) => {
+            let key_uri = state.chain.get_authentication_key_uri().await?;
+            let quote = crate::kilt::create_quote(
+                &quote_config,
+                &crate::kilt::get_did_uri(&state.attester_did),
+                &ctype_hash,
+            );
+            Some(crate::kilt::sign_quote(
+                quote,
+                &key_uri,
+                &state.authentication_signer,
+            )?)
+        }
+        None => None,
+    };
+
     let content = SubmitTermsMessageContent {
         claim,
         legitimations: Some(vec![]),
+        required_legitimations: ctype_config.legitimations,
+        delegation_id: ctype_config.delegation_id,
+        quote,
     };
 
-    let msg = Message {
-        body: MessageBody {
-            content,
-            type_: "submit-terms".to_string(),
-        },
-        created_at: 0,
-        sender: sender.to_string(),
-        receiver: sender_key_uri.clone(),
-        message_id: uuid::Uuid::new_v4().to_string(),
-        in_reply_to: None,
-        references: None,
-    };
+    let msg = Message::new(content, get_sender(&state)?, &sender_key_uri, None);
 
-    let msg_json = serde_json::to_string(&msg).unwrap();
-    let msg_bytes = msg_json.as_bytes();
-    let our_secretkey = state.encryption_key.clone();
-    let nonce = box_::gen_nonce();
-    let encrypted_msg = box_::seal(msg_bytes, &nonce, &others_pubkey, &our_secretkey);
-    let response = EncryptedMessage {
-        cipher_text: encrypted_msg,
-        nonce,
-        sender_key_uri: state.session.key_uri.clone(),
-        receiver_key_uri: sender_key_uri,
-    };
+    let response = encrypt_message(&msg, &others_pubkey, &state)?;
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    request_body = EncryptedMessage,
+    responses(
+        (status = 200, description = "The encrypted submit-attestation or reject-attestation message", body = EncryptedMessage),
+        (status = 402, description = "The encrypted request-payment message", body = EncryptedMessage),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
 #[post("/{session}/{attestation_id}")]
 async fn request_attestation(
     state: web::Data<AppState>,
@@ -105,66 +132,611 @@ async fn request_attestation(
 
     let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
 
-    if attestation.approved_at.is_none() {
-        Err(actix_web::error::ErrorBadRequest(
-            "Attestation is not approved",
+    let (message, others_pubkey) = decrypt_message(&encrypted_message, &state).await?;
+
+    // The claimer can decline the terms instead of requesting the attestation.
+    if message.body.type_ == RejectMessageContent::TYPE {
+        remove_session(&state.db_executor, &session_id).await?;
+        let content = message.into_content::<RejectMessageContent>()?.body.content;
+        log::info!(
+            "Claimer declined the terms for attestation request {}: {:?}",
+            attestation_id,
+            content.message
+        );
+        return Err(AppError::Message(format!(
+            "Claimer declined the terms: {}",
+            content.message.unwrap_or_default()
+        )));
+    }
+
+    // The wallet of the claimer reports an error, if it could not process the terms.
+    if message.body.type_ == ErrorMessageContent::TYPE {
+        remove_session(&state.db_executor, &session_id).await?;
+        let content = message.into_content::<ErrorMessageContent>()?.body.content;
+        log::info!(
+            "Wallet of the claimer reported an error for attestation request {}: {:?} {:?}",
+            attestation_id,
+            content.name,
+            content.message
+        );
+        return Err(AppError::Message(format!(
+            "Wallet of the claimer reported an error: {}",
+            content.message.or(content.name).unwrap_or_default()
+        )));
+    }
+
+    let decrypted_message = message.into_content::<RequestAttestationMessageContent>()?;
+
+    let credential = decrypted_message.body.content.credential;
+
+    // only the reviewed credential of the attestation request can be attested.
+    let reviewed_credential: Credential = serde_json::from_value(attestation.credential.clone())?;
+    if !credential
+        .root_hash
+        .eq_ignore_ascii_case(&reviewed_credential.root_hash)
+    {
+        Err(AppError::Conflict(
+            "Credential does not match the reviewed attestation request",
         ))?
     }
 
+    // The root hash commits to the claim, the legitimations and the delegation id, so the
+    // presented credential has to be consistent with it. Only the stored data is used from here.
+    crate::kilt::verify_credential_hashes(&credential)?;
+    let credential = reviewed_credential;
+
+    if attestation.status == AttestationState::Rejected {
+        remove_session(&state.db_executor, &session_id).await?;
+        let msg = Message::new(
+            RejectAttestationMessageContent(credential.root_hash),
+            get_sender(&state)?,
+            &encrypted_message.sender_key_uri,
+            Some(decrypted_message.message_id),
+        );
+        let response = encrypt_message(&msg, &others_pubkey, &state)?;
+        return Ok(HttpResponse::Ok().json(response));
+    }
+
+    if attestation.status != AttestationState::MarkedApproved {
+        Err(AppError::Conflict("Attestation is not approved"))?
+    }
+
+    let ctype_config = get_ctype_config(&state.ctypes, &attestation.ctype_hash);
+
+    crate::kilt::verify_legitimations(
+        &credential,
+        &ctype_config.legitimations,
+        state.chain.as_ref(),
+    )
+    .await?;
+
+    let delegation_id = crate::kilt::get_delegation_id(
+        ctype_config.delegation_id.as_deref(),
+        credential.delegation_id.as_deref(),
+    )?;
+
+    if let Some(quote_config) = ctype_config.quote {
+        let agreement = decrypted_message
+            .body
+            .content
+            .quote
+            .ok_or(AppError::Quote("Quote has to be accepted"))?;
+
+        let expected_quote = crate::kilt::create_quote(
+            &quote_config,
+            &crate::kilt::get_did_uri(&state.attester_did),
+            &attestation.ctype_hash,
+        );
+
+        if agreement.attester_signed_quote.quote != expected_quote {
+            Err(AppError::Quote("Quote does not match the offered terms"))?
+        }
+
+        crate::kilt::verify_attester_signature(
+            &agreement.attester_signed_quote,
+            &state.authentication_signer,
+        )?;
+        crate::kilt::verify_quote_agreement(
+            &agreement,
+            &credential.root_hash,
+            &credential.claim.owner,
+            state.chain.as_ref(),
+        )
+        .await?;
+
+        if quote_config.require_payment {
+            upsert_quote_agreement(
+                &attestation_id,
+                &serde_json::to_value(&agreement)?,
+                &state.db_executor,
+            )
+            .await?;
+
+            let msg = Message::new(
+                RequestPaymentMessageContent {
+                    claim_hash: credential.root_hash,
+                },
+                get_sender(&state)?,
+                &encrypted_message.sender_key_uri,
+                Some(decrypted_message.message_id),
+            );
+
+            let response = encrypt_message(&msg, &others_pubkey, &state)?;
+
+            return Ok(HttpResponse::PaymentRequired().json(response));
+        }
+    }
+
+    // Like an approval, the request is locked and marked as in flight before the claim is
+    // submitted, so that concurrent requests can not submit it twice.
+    let mut db_tx = state.db_executor.begin().await?;
+    let attestation = can_attest_marked_approved_tx(&attestation_id, &mut db_tx).await?;
+    mark_attestation_request_in_flight(&attestation_id, &mut *db_tx).await?;
+    db_tx.commit().await?;
+
     remove_session(&state.db_executor, &session_id).await?;
-    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
 
-    let others_pubkey = crate::kilt::get_encryption_key_from_fulldid_key_uri(
+    let result = attest_credential(
+        &credential.root_hash,
+        &attestation.ctype_hash,
+        delegation_id,
+        &state,
+    )
+    .await;
+
+    let mut db_tx = state.db_executor.begin().await?;
+    let attestation = match result {
+        Ok(attestation) => attestation,
+        Err(e) => {
+            record_attestation_request_failed(
+                &attestation_id,
+                AttestationState::MarkedApproved,
+                &mut db_tx,
+            )
+            .await?;
+            db_tx.commit().await?;
+            return Err(e);
+        }
+    };
+    approve_attestation_request(&attestation_id, &mut db_tx).await?;
+    db_tx.commit().await?;
+
+    purge_evidences(&attestation_id, &state).await;
+
+    let msg = Message::new(
+        SubmitAttestationMessageContent { attestation },
+        get_sender(&state)?,
         &encrypted_message.sender_key_uri,
-        &chain_client,
+        Some(decrypted_message.message_id),
+    );
+    let response = encrypt_message(&msg, &others_pubkey, &state)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    request_body = EncryptedMessage,
+    responses(
+        (status = 200, description = "The encrypted submit-attestation message", body = EncryptedMessage),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+    )
+)]
+#[post("/payment/{session}/{attestation_id}")]
+async fn confirm_payment(
+    state: web::Data<AppState>,
+    encrypted_message: web::Json<EncryptedMessage>,
+    param: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, AppError> {
+    let session_id = param.0;
+    let attestation_id = param.1;
+
+    get_session(&state.db_executor, &session_id).await?;
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+
+    if attestation.status != AttestationState::MarkedApproved {
+        Err(AppError::Conflict("Attestation is not approved"))?
+    }
+
+    let ctype_config = get_ctype_config(&state.ctypes, &attestation.ctype_hash);
+    let quote_config = ctype_config
+        .quote
+        .ok_or(AppError::Payment("No payment is required for this CType"))?;
+
+    if quote_config.currency != "KILT" {
+        Err(AppError::Payment("Only payments in KILT are supported"))?
+    }
+
+    let amount = crate::kilt::kilt_to_femto(&quote_config.cost.gross)
+        .ok_or(AppError::Payment("Gross cost is not a valid KILT amount"))?;
+
+    let accepted_quote = get_quote_agreement(&attestation_id, &state.db_executor).await?;
+    let agreement: QuoteAgreement = serde_json::from_value(accepted_quote.agreement)?;
+
+    let (message, others_pubkey) = decrypt_message(&encrypted_message, &state).await?;
+    let decrypted_message = message.into_content::<ConfirmPaymentMessageContent>()?;
+
+    let payment = decrypted_message.body.content;
+
+    if !payment
+        .claim_hash
+        .eq_ignore_ascii_case(&agreement.root_hash)
+    {
+        Err(AppError::Payment("Payment was made for another credential"))?
+    }
+
+    let block_hash = parse_hash(&payment.block_hash)?;
+    let tx_hash = parse_hash(&payment.tx_hash)?;
+
+    crate::kilt::verify_payment(
+        block_hash,
+        tx_hash,
+        &AccountId32(state.payer.account_id().clone().into()),
+        amount,
+        state.chain.as_ref(),
+    )
+    .await?;
+
+    let mut db_tx = state.db_executor.begin().await?;
+
+    let result = mark_quote_paid(
+        &attestation_id,
+        &format!("{:?}", block_hash),
+        &format!("{:?}", tx_hash),
+        &mut db_tx,
     )
     .await?;
 
+    if result.rows_affected() == 0 {
+        Err(AppError::Payment("Payment was already used"))?
+    }
+
+    // The delegation of the credential was checked when the quote agreement was received.
+    let delegation_id = ctype_config
+        .delegation_id
+        .as_deref()
+        .map(crate::kilt::parse_delegation_id)
+        .transpose()?;
+
+    let attestation = attest_credential(
+        &agreement.root_hash,
+        &agreement.attester_signed_quote.quote.ctype_hash,
+        delegation_id,
+        &state,
+    )
+    .await?;
+
+    approve_attestation_request(&attestation_id, &mut db_tx).await?;
+    db_tx.commit().await?;
+
+    purge_evidences(&attestation_id, &state).await;
+    remove_session(&state.db_executor, &session_id).await?;
+
+    let msg = Message::new(
+        SubmitAttestationMessageContent { attestation },
+        get_sender(&state)?,
+        &encrypted_message.sender_key_uri,
+        Some(decrypted_message.message_id),
+    );
+    let response = encrypt_message(&msg, &others_pubkey, &state)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    responses(
+        (status = 200, description = "The encrypted submit-attestation message", body = EncryptedMessage),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
+#[post("/attestation/{session_id}/{attestation_id}")]
+async fn deliver_attestation(
+    state: web::Data<AppState>,
+    user: ReqData<User>,
+    param: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, AppError> {
+    let session_id = param.0;
+    let attestation_id = param.1;
+
+    let session = get_session(&state.db_executor, &session_id).await?;
+
+    let receiver_key_uri = session
+        .encryption_key_uri
+        .ok_or(AppError::Conflict("Session set up not completed"))?;
+    let others_pubkey = crate::kilt::parse_encryption_key_from_lightdid(&receiver_key_uri)?;
+
+    let attestation_request =
+        get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+
+    if attestation_request.claimer != user.id {
+        Err(AppError::Forbidden(
+            "Only the claimer can receive the attestation",
+        ))?
+    }
+
+    let attestation_message = get_attestation_message(&attestation_id, &state.db_executor).await?;
+    let mut attestation: Attestation = serde_json::from_value(attestation_message.content)?;
+    attestation.revoked = attestation_request.status == AttestationState::Revoked;
+
+    let msg = Message::new(
+        SubmitAttestationMessageContent { attestation },
+        get_sender(&state)?,
+        &receiver_key_uri,
+        None,
+    );
+    let response = encrypt_message(&msg, &others_pubkey, &state)?;
+
+    mark_attestation_message_delivered(&attestation_id, &state.db_executor).await?;
+    remove_session(&state.db_executor, &session_id).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    request_body = [CTypeRequest],
+    responses(
+        (status = 200, description = "The encrypted request-credential message", body = EncryptedMessage),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+    )
+)]
+#[post("/request-credential/{session_id}")]
+async fn request_credential(
+    state: web::Data<AppState>,
+    user: ReqData<User>,
+    ctypes: web::Json<Vec<CTypeRequest>>,
+    session_id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let session = get_session(&state.db_executor, &session_id).await?;
+
+    let receiver_key_uri = session
+        .encryption_key_uri
+        .ok_or(AppError::Conflict("Session set up not completed"))?;
+    let others_pubkey = crate::kilt::parse_encryption_key_from_lightdid(&receiver_key_uri)?;
+
+    if ctypes.is_empty() {
+        Err(AppError::BadRequest(
+            "At least one CType has to be requested",
+        ))?
+    }
+
+    let challenge = format!("0x{}", hex::encode(randombytes::randombytes(32)));
+
+    insert_verification_request(
+        &session_id,
+        &user.id,
+        &serde_json::to_value(&ctypes.0)?,
+        &challenge,
+        &state.db_executor,
+    )
+    .await?;
+
+    let content = RequestCredentialMessageContent {
+        ctypes: ctypes.into_inner(),
+        challenge: Some(challenge),
+    };
+
+    let msg = Message::new(content, get_sender(&state)?, &receiver_key_uri, None);
+    let response = encrypt_message(&msg, &others_pubkey, &state)?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    request_body = EncryptedMessage,
+    responses(
+        (status = 200, description = "The verification request with the verified credentials", body = VerificationRequest),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+    )
+)]
+#[post("/submit-credential/{session_id}")]
+async fn submit_credential(
+    state: web::Data<AppState>,
+    user: ReqData<User>,
+    encrypted_message: web::Json<EncryptedMessage>,
+    session_id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let verification_request =
+        get_open_verification_request(&session_id, &state.db_executor).await?;
+
+    if verification_request.requested_by != user.id {
+        Err(AppError::Forbidden(
+            "User is not allowed to submit credentials for this verification request",
+        ))?
+    }
+
+    let requested_ctypes: Vec<CTypeRequest> = serde_json::from_value(verification_request.ctypes)?;
+
+    let (message, _) = decrypt_message(&encrypted_message, &state).await?;
+    let presentations = message
+        .into_content::<SubmitCredentialMessageContent>()?
+        .body
+        .content
+        .0;
+
+    if presentations.is_empty() {
+        Err(AppError::Credential(
+            "No credential was presented".to_string(),
+        ))?
+    }
+
+    let mut verified_credentials = Vec::with_capacity(presentations.len());
+    for presentation in presentations.iter() {
+        verified_credentials.push(
+            crate::kilt::verify_presentation(
+                presentation,
+                &verification_request.challenge,
+                &requested_ctypes,
+                state.chain.as_ref(),
+            )
+            .await?,
+        );
+    }
+
+    let verification_request = complete_verification_request(
+        &verification_request.id,
+        &serde_json::to_value(&verified_credentials)?,
+        &state.db_executor,
+    )
+    .await?;
+
+    remove_session(&state.db_executor, &session_id).await?;
+
+    Ok(HttpResponse::Ok().json(verification_request))
+}
+
+#[utoipa::path(
+    context_path = routes::CREDENTIAL,
+    tag = "credential",
+    responses(
+        (status = 200, description = "The verification request", body = VerificationRequest),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 404, description = "The resource does not exist", body = ErrorResponse),
+    )
+)]
+#[get("/verification/{verification_request_id}")]
+async fn get_verification(
+    state: web::Data<AppState>,
+    user: ReqData<User>,
+    verification_request_id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let verification_request =
+        get_verification_request(&verification_request_id, &state.db_executor).await?;
+
+    if verification_request.requested_by != user.id && !user.is_admin {
+        Err(AppError::Forbidden(
+            "User is not allowed to see this verification request",
+        ))?
+    }
+
+    Ok(HttpResponse::Ok().json(verification_request))
+}
+
+fn parse_hash(hash: &str) -> Result<H256, AppError> {
+    let bytes = hex::decode(hash.trim_start_matches("0x").trim())?;
+    if bytes.len() != 32 {
+        Err(AppError::BadRequest("Hash has a wrong format"))?
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+fn get_sender(state: &AppState) -> Result<&str, AppError> {
+    state
+        .session
+        .key_uri
+        .split('#')
+        .next()
+        .ok_or_else(|| AppError::Attestation("Invalid Key URI for sender"))
+}
+
+fn encrypt_message<T: Serialize>(
+    msg: &Message<T>,
+    others_pubkey: &box_::PublicKey,
+    state: &AppState,
+) -> Result<EncryptedMessage, AppError> {
+    let msg_json = serde_json::to_string(msg)?;
+    let nonce = box_::gen_nonce();
+    let encrypted_msg = box_::seal(
+        msg_json.as_bytes(),
+        &nonce,
+        others_pubkey,
+        &state.encryption_key,
+    );
+    Ok(EncryptedMessage {
+        cipher_text: encrypted_msg,
+        nonce,
+        sender_key_uri: state.session.key_uri.clone(),
+        receiver_key_uri: msg.receiver.clone(),
+    })
+}
+
+async fn decrypt_message(
+    encrypted_message: &EncryptedMessage,
+    state: &AppState,
+) -> Result<(Message<serde_json::Value>, box_::PublicKey), AppError> {
+    let others_pubkey = if encrypted_message
+        .sender_key_uri
+        .starts_with("did:kilt:light:")
+    {
+        crate::kilt::parse_encryption_key_from_lightdid(&encrypted_message.sender_key_uri)?
+    } else {
+        state
+            .chain
+            .get_encryption_key(&encrypted_message.sender_key_uri)
+            .await?
+    };
+
     let decrypted_message_bytes = box_::open(
         &encrypted_message.cipher_text,
         &encrypted_message.nonce,
         &others_pubkey,
         &state.encryption_key,
     )
-    .map_err(|_| AppError::Attestation("Unable to decrypt"))?;
-
-    let decrypted_message: Message<RequestAttestationMessageContent> =
-        serde_json::from_slice(&decrypted_message_bytes).unwrap();
+    .map_err(|_| AppError::BadRequest("Unable to decrypt the message"))?;
 
-    let credential = decrypted_message.body.content.credential;
+    Ok((
+        serde_json::from_slice(&decrypted_message_bytes)?,
+        others_pubkey,
+    ))
+}
 
-    let ctype_hash = hex::decode(credential.claim.ctype_hash.trim_start_matches("0x").trim())?;
-    let claim_hash = hex::decode(credential.root_hash.trim_start_matches("0x").trim())?;
+async fn attest_credential(
+    root_hash: &str,
+    ctype_hash: &str,
+    delegation_id: Option<H256>,
+    state: &AppState,
+) -> Result<Attestation, AppError> {
+    let ctype_hash = hex::decode(ctype_hash.trim_start_matches("0x").trim())?;
+    let claim_hash = hex::decode(root_hash.trim_start_matches("0x").trim())?;
     if claim_hash.len() != 32 || ctype_hash.len() != 32 {
-        Err(actix_web::error::ErrorBadRequest(
+        Err(AppError::BadRequest(
             "Claim hash or ctype hash have a wrong format",
         ))?
     }
 
-    let payer = state.payer.clone();
-    let did = state.attester_did.clone();
-    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
-    let signer = state.signer.clone();
-
-    crate::kilt::create_claim(
-        H256::from_slice(&claim_hash),
-        H256::from_slice(&ctype_hash),
-        &did,
-        &chain_client,
-        &payer,
-        &signer,
-    )
-    .await?;
+    if let Some(delegation_id) = delegation_id {
+        crate::kilt::verify_delegation_node(
+            delegation_id,
+            &state.attester_did,
+            H256::from_slice(&ctype_hash),
+            state.chain.as_ref(),
+        )
+        .await?;
+    }
 
-    let mut db_tx = state.db_executor.begin().await?;
-    approve_attestation_request(&attestation_id, &mut db_tx).await?;
-    db_tx.commit().await?;
+    state
+        .chain
+        .create_claim(
+            H256::from_slice(&claim_hash),
+            H256::from_slice(&ctype_hash),
+            delegation_id,
+        )
+        .await?;
 
-    Ok(HttpResponse::Ok().json("ok"))
+    Ok(Attestation {
+        claim_hash: root_hash.to_string(),
+        ctype_hash: format!("0x{}", hex::encode(ctype_hash)),
+        owner: crate::kilt::get_did_uri(&state.attester_did),
+        delegation_id: delegation_id.map(|id| format!("{:?}", id)),
+        revoked: false,
+    })
 }
 
 pub fn get_credential_scope() -> Scope {
-    web::scope("/api/v1/credential")
+    web::scope(routes::CREDENTIAL)
         .service(send_terms)
+        .service(confirm_payment)
+        .service(deliver_attestation)
+        .service(request_credential)
+        .service(submit_credential)
+        .service(get_verification)
         .service(request_attestation)
 }
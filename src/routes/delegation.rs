@@ -0,0 +1,237 @@
+use actix_web::{
+    get, post, put,
+    web::{self, ReqData},
+    HttpResponse, Scope,
+};
+use attester_api::routes;
+use sodiumoxide::randombytes;
+use subxt::{ext::sp_core::H256, OnlineClient};
+
+use crate::{
+    auth::User,
+    database::dto::{
+        DelegationHierarchy, DelegationRequest, DelegationSigningPayload, ErrorResponse,
+        NewDelegation, NewDelegationHierarchy,
+    },
+    error::AppError,
+    kilt::{
+        add_delegation, create_delegation_hierarchy, get_authentication_signature,
+        get_delegation_creation_hash, get_delegation_hierarchy, get_hierarchy_root_id,
+        parse_delegation_id, parse_did, revoke_delegation, KiltConfig,
+    },
+    utils::is_user_admin,
+    AppState,
+};
+
+#[utoipa::path(
+    context_path = routes::DELEGATION,
+    tag = "delegation",
+    responses(
+        (status = 200, description = "The delegation hierarchy", body = DelegationHierarchy),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 404, description = "The resource does not exist", body = ErrorResponse),
+    )
+)]
+#[get("/{root_id}")]
+async fn get_hierarchy(
+    root_id: web::Path<String>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden(
+            "Only admins can see delegation hierarchies",
+        ))?
+    }
+
+    let root_id = parse_delegation_id(&root_id)?;
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    let hierarchy = get_delegation_hierarchy(root_id, &chain_client).await?;
+    Ok(HttpResponse::Ok().json(hierarchy))
+}
+
+#[utoipa::path(
+    context_path = routes::DELEGATION,
+    tag = "delegation",
+    request_body = NewDelegationHierarchy,
+    responses(
+        (status = 200, description = "The created delegation hierarchy", body = DelegationHierarchy),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[post("")]
+async fn post_hierarchy(
+    body: web::Json<NewDelegationHierarchy>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden(
+            "Only admins can create delegation hierarchies",
+        ))?
+    }
+
+    let ctype_hash = hex::decode(body.ctype_hash.trim_start_matches("0x").trim())?;
+    if ctype_hash.len() != 32 {
+        Err(AppError::BadRequest("CType hash has a wrong format"))?
+    }
+
+    let root_id = H256::from_slice(&randombytes::randombytes(32));
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    create_delegation_hierarchy(
+        root_id,
+        H256::from_slice(&ctype_hash),
+        &state.attester_did,
+        &chain_client,
+        &state.payer,
+        &state.delegation_signer,
+    )
+    .await?;
+
+    let hierarchy = get_delegation_hierarchy(root_id, &chain_client).await?;
+    Ok(HttpResponse::Ok().json(hierarchy))
+}
+
+/// Creates a new delegation ID below the parent node and returns the hash which the delegate has
+/// to sign with its authentication key.
+#[utoipa::path(
+    context_path = routes::DELEGATION,
+    tag = "delegation",
+    request_body = DelegationRequest,
+    responses(
+        (status = 200, description = "The payload which the delegate has to sign", body = DelegationSigningPayload),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[post("/{parent_id}/children/payload")]
+async fn post_delegation_payload(
+    parent_id: web::Path<String>,
+    body: web::Json<DelegationRequest>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can add delegations"))?
+    }
+
+    parse_did(&body.delegate)?;
+    let parent_id = parse_delegation_id(&parent_id)?;
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    let hierarchy_root_id = get_hierarchy_root_id(parent_id, &chain_client).await?;
+
+    let delegation_id = H256::from_slice(&randombytes::randombytes(32));
+    let hash = get_delegation_creation_hash(
+        delegation_id,
+        hierarchy_root_id,
+        parent_id,
+        body.permissions.into(),
+    );
+
+    Ok(HttpResponse::Ok().json(DelegationSigningPayload {
+        delegation_id: format!("{:?}", delegation_id),
+        hierarchy_root_id: format!("{:?}", hierarchy_root_id),
+        hash: format!("{:?}", hash),
+    }))
+}
+
+#[utoipa::path(
+    context_path = routes::DELEGATION,
+    tag = "delegation",
+    request_body = NewDelegation,
+    responses(
+        (status = 200, description = "The delegation hierarchy with the new node", body = DelegationHierarchy),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+    )
+)]
+#[post("/{parent_id}/children")]
+async fn post_delegation(
+    parent_id: web::Path<String>,
+    body: web::Json<NewDelegation>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can add delegations"))?
+    }
+
+    let parent_id = parse_delegation_id(&parent_id)?;
+    let delegation_id = parse_delegation_id(&body.delegation_id)?;
+    let delegate = parse_did(&body.delegate)?;
+    let permissions: u32 = body.permissions.into();
+
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    let hierarchy_root_id = get_hierarchy_root_id(parent_id, &chain_client).await?;
+    let hash =
+        get_delegation_creation_hash(delegation_id, hierarchy_root_id, parent_id, permissions);
+
+    let delegate_signature = get_authentication_signature(
+        hash.as_bytes(),
+        &body.delegate_signature,
+        &delegate,
+        &chain_client,
+    )
+    .await?;
+
+    add_delegation(
+        delegation_id,
+        parent_id,
+        delegate,
+        permissions,
+        delegate_signature,
+        &state.attester_did,
+        &chain_client,
+        &state.payer,
+        &state.delegation_signer,
+    )
+    .await?;
+
+    let hierarchy = get_delegation_hierarchy(hierarchy_root_id, &chain_client).await?;
+    Ok(HttpResponse::Ok().json(hierarchy))
+}
+
+#[utoipa::path(
+    context_path = routes::DELEGATION,
+    tag = "delegation",
+    responses(
+        (status = 200, description = "The delegation hierarchy with the revoked node", body = DelegationHierarchy),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[put("/{delegation_id}/revoke")]
+async fn revoke_delegation_node(
+    delegation_id: web::Path<String>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can revoke delegations"))?
+    }
+
+    let delegation_id = parse_delegation_id(&delegation_id)?;
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    let hierarchy_root_id = get_hierarchy_root_id(delegation_id, &chain_client).await?;
+
+    revoke_delegation(
+        delegation_id,
+        &state.attester_did,
+        &chain_client,
+        &state.payer,
+        &state.delegation_signer,
+    )
+    .await?;
+
+    log::info!("Delegation node {:?} is revoked", delegation_id);
+
+    let hierarchy = get_delegation_hierarchy(hierarchy_root_id, &chain_client).await?;
+    Ok(HttpResponse::Ok().json(hierarchy))
+}
+
+pub fn get_delegation_scope() -> Scope {
+    web::scope(routes::DELEGATION)
+        .service(post_hierarchy)
+        .service(get_hierarchy)
+        .service(post_delegation_payload)
+        .service(post_delegation)
+        .service(revoke_delegation_node)
+}
@@ -0,0 +1,187 @@
+use actix_web::{
+    delete, get, post, put,
+    web::{self, ReqData},
+    HttpResponse, Scope,
+};
+use attester_api::routes;
+use subxt::{ext::sp_core::Pair, OnlineClient};
+
+use crate::{
+    auth::User,
+    database::dto::{DidDocument, ErrorResponse, NewServiceEndpoint},
+    error::AppError,
+    kilt::{get_did_document, parse_key_id, update_did, DidUpdate, KiltConfig},
+    utils::is_user_admin,
+    AppState,
+};
+
+/// Submits the update of the attester DID and returns the resulting DID document.
+async fn apply_update(
+    update: DidUpdate,
+    user: &User,
+    state: &AppState,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(user) {
+        Err(AppError::Forbidden(
+            "Only admins can update the attester DID",
+        ))?
+    }
+
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    update_did(
+        update,
+        &state.attester_did,
+        &chain_client,
+        &state.payer,
+        &state.authentication_signer,
+    )
+    .await?;
+
+    let document = get_did_document(&state.attester_did, &chain_client).await?;
+    Ok(HttpResponse::Ok().json(document))
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    responses(
+        (status = 200, description = "The attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("")]
+async fn get_did(
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    if !is_user_admin(&user) {
+        Err(AppError::Forbidden("Only admins can see the attester DID"))?
+    }
+
+    let chain_client = OnlineClient::<KiltConfig>::from_url(&state.endpoint).await?;
+    let document = get_did_document(&state.attester_did, &chain_client).await?;
+    Ok(HttpResponse::Ok().json(document))
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    responses(
+        (status = 200, description = "The updated attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[put("/attestation_key")]
+async fn set_attestation_key(
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let update = DidUpdate::SetAttestationKey(state.signer.signer().public());
+    apply_update(update, &user, &state).await
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    responses(
+        (status = 200, description = "The updated attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[put("/delegation_key")]
+async fn set_delegation_key(
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let update = DidUpdate::SetDelegationKey(state.delegation_signer.signer().public());
+    apply_update(update, &user, &state).await
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    responses(
+        (status = 200, description = "The updated attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[post("/key_agreement_keys")]
+async fn add_key_agreement_key(
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let update = DidUpdate::AddKeyAgreementKey(state.encryption_key.public_key());
+    apply_update(update, &user, &state).await
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    responses(
+        (status = 200, description = "The updated attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[delete("/key_agreement_keys/{key_id}")]
+async fn remove_key_agreement_key(
+    key_id: web::Path<String>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let update = DidUpdate::RemoveKeyAgreementKey(parse_key_id(&key_id)?);
+    apply_update(update, &user, &state).await
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    request_body = NewServiceEndpoint,
+    responses(
+        (status = 200, description = "The updated attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+    )
+)]
+#[post("/services")]
+async fn add_service_endpoint(
+    body: web::Json<NewServiceEndpoint>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let body = body.into_inner();
+    let update = DidUpdate::AddServiceEndpoint {
+        id: body.id,
+        service_types: body.service_types,
+        urls: body.urls,
+    };
+    apply_update(update, &user, &state).await
+}
+
+#[utoipa::path(
+    context_path = routes::DID,
+    tag = "did",
+    responses(
+        (status = 200, description = "The updated attester DID", body = DidDocument),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[delete("/services/{service_id}")]
+async fn remove_service_endpoint(
+    service_id: web::Path<String>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let update = DidUpdate::RemoveServiceEndpoint(service_id.into_inner());
+    apply_update(update, &user, &state).await
+}
+
+pub fn get_did_scope() -> Scope {
+    web::scope(routes::DID)
+        .service(get_did)
+        .service(set_attestation_key)
+        .service(set_delegation_key)
+        .service(add_key_agreement_key)
+        .service(remove_key_agreement_key)
+        .service(add_service_endpoint)
+        .service(remove_service_endpoint)
+}
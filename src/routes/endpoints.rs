@@ -1,7 +1,16 @@
 use actix_web::{get, web, HttpResponse, Scope};
+use attester_api::routes;
 
 use crate::{error::AppError, AppState};
 
+#[utoipa::path(
+    context_path = routes::ENDPOINTS,
+    tag = "endpoints",
+    security(()),
+    responses(
+        (status = 200, description = "The URLs of the OpenDID and the KILT node", body = [String]),
+    )
+)]
 #[get("")]
 async fn get_endpoints(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
     let auth_endpoint = state.auth_url.clone();
@@ -10,5 +19,5 @@ async fn get_endpoints(state: web::Data<AppState>) -> Result<HttpResponse, AppEr
 }
 
 pub fn get_endpoint_scope() -> Scope {
-    web::scope("/api/v1/endpoints").service(get_endpoints)
+    web::scope(routes::ENDPOINTS).service(get_endpoints)
 }
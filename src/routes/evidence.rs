@@ -0,0 +1,174 @@
+use actix_web::{
+    get,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    post,
+    web::{self, ReqData},
+    HttpMessage, HttpRequest, HttpResponse, Scope,
+};
+use attester_api::routes;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    database::{
+        dto::{AttestationState, ErrorResponse, Evidence, EvidenceUpload},
+        querys::{get_attestation_request_by_id, get_evidence, get_evidences, insert_evidence},
+    },
+    error::AppError,
+    evidence::EvidenceStore,
+    utils::is_user_allowed_to_access_evidence,
+    AppState,
+};
+
+fn get_evidence_store(state: &AppState) -> Result<&EvidenceStore, AppError> {
+    state
+        .evidence_store
+        .as_ref()
+        .ok_or(AppError::Evidence("Evidence storage is not configured"))
+}
+
+#[utoipa::path(
+    context_path = routes::EVIDENCE,
+    tag = "evidence",
+    params(EvidenceUpload),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "The stored evidence", body = Evidence),
+        (status = 400, description = "The request is invalid", body = ErrorResponse),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 409, description = "The action is not possible in the current state", body = ErrorResponse),
+        (status = 413, description = "The file is too large", body = ErrorResponse),
+    )
+)]
+#[post("/{attestation_request_id}")]
+async fn upload_evidence(
+    attestation_id: web::Path<Uuid>,
+    upload: web::Query<EvidenceUpload>,
+    req: HttpRequest,
+    body: web::Bytes,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let file_name = upload.file_name.trim();
+    if file_name.is_empty() || file_name.len() > 255 || body.is_empty() {
+        Err(AppError::BadRequest(
+            "A file name and a non empty file are required",
+        ))?
+    }
+
+    let evidence_store = get_evidence_store(&state)?;
+    let attestation = get_attestation_request_by_id(&attestation_id, &state.db_executor).await?;
+
+    // only the claimer can provide evidence for the own request.
+    if attestation.claimer != user.id {
+        Err(AppError::Forbidden("Only the claimer can upload evidences"))?
+    }
+
+    if !matches!(
+        attestation.status,
+        AttestationState::Pending | AttestationState::ChangesRequested
+    ) {
+        Err(AppError::Conflict(
+            "Evidence can only be added to pending attestation requests",
+        ))?
+    }
+
+    let content_type = match req.content_type() {
+        "" => "application/octet-stream",
+        content_type => content_type,
+    };
+
+    let mut tx = state.db_executor.begin().await?;
+    let evidence = insert_evidence(
+        &attestation_id,
+        file_name,
+        content_type,
+        body.len() as i64,
+        &user.id,
+        &mut tx,
+    )
+    .await?;
+    evidence_store.save(&evidence.id, &body).await?;
+    tx.commit().await?;
+
+    log::info!(
+        "Evidence {:?} for attestation with id {:?} is uploaded",
+        evidence.id,
+        attestation_id
+    );
+    Ok(HttpResponse::Ok().json(evidence))
+}
+
+#[utoipa::path(
+    context_path = routes::EVIDENCE,
+    tag = "evidence",
+    responses(
+        (status = 200, description = "The evidences of the attestation request", body = [Evidence]),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+    )
+)]
+#[get("/{attestation_request_id}")]
+async fn get_attestation_evidences(
+    attestation_id: web::Path<Uuid>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let is_user_allowed =
+        is_user_allowed_to_access_evidence(&user, &attestation_id, &state.db_executor).await?;
+
+    if !is_user_allowed {
+        Err(AppError::Forbidden(
+            "User is not allowed to see the evidences of this attestation request",
+        ))?
+    }
+
+    let evidences = get_evidences(&attestation_id, &state.db_executor).await?;
+    Ok(HttpResponse::Ok().json(serde_json::to_value(&evidences)?))
+}
+
+#[utoipa::path(
+    context_path = routes::EVIDENCE,
+    tag = "evidence",
+    responses(
+        (status = 200, description = "The content of the evidence", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 403, description = "The user is not allowed to access the resource", body = ErrorResponse),
+        (status = 404, description = "The resource does not exist", body = ErrorResponse),
+    )
+)]
+#[get("/{attestation_request_id}/{evidence_id}")]
+async fn download_evidence(
+    param: web::Path<(Uuid, Uuid)>,
+    user: ReqData<User>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (attestation_id, evidence_id) = param.into_inner();
+    let evidence_store = get_evidence_store(&state)?;
+
+    let is_user_allowed =
+        is_user_allowed_to_access_evidence(&user, &attestation_id, &state.db_executor).await?;
+
+    if !is_user_allowed {
+        Err(AppError::Forbidden(
+            "User is not allowed to see the evidences of this attestation request",
+        ))?
+    }
+
+    let evidence = get_evidence(&attestation_id, &evidence_id, &state.db_executor).await?;
+    let content = evidence_store.read(&evidence.id).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(evidence.content_type)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(evidence.file_name)],
+        })
+        .body(content))
+}
+
+pub fn get_evidence_scope(max_file_size: usize) -> Scope {
+    web::scope(routes::EVIDENCE)
+        .app_data(web::PayloadConfig::new(max_file_size))
+        .service(upload_evidence)
+        .service(get_attestation_evidences)
+        .service(download_evidence)
+}
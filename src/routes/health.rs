@@ -0,0 +1,73 @@
+use actix_web::{get, web, HttpResponse, Scope};
+
+use crate::{
+    error::AppError,
+    health::{check_readiness, run_self_check, ReadinessReport, SelfCheckReport},
+    AppState,
+};
+
+#[utoipa::path(
+    context_path = "/health",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The attester is configured correctly", body = SelfCheckReport),
+        (status = 503, description = "The attester is misconfigured", body = SelfCheckReport),
+    )
+)]
+#[get("")]
+async fn get_health(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let report = match run_self_check(&state).await {
+        Ok(report) => report,
+        Err(e) => {
+            log::error!("Self-check failed: {}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(e.to_string()));
+        }
+    };
+
+    if report.is_healthy() {
+        Ok(HttpResponse::Ok().json(report))
+    } else {
+        Ok(HttpResponse::ServiceUnavailable().json(report))
+    }
+}
+
+#[utoipa::path(
+    context_path = "/health",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The service is running", body = serde_json::Value),
+    )
+)]
+#[get("/live")]
+async fn get_liveness() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+#[utoipa::path(
+    context_path = "/health",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The database and the chain are reachable", body = ReadinessReport),
+        (status = 503, description = "A dependency is unavailable", body = ReadinessReport),
+    )
+)]
+#[get("/ready")]
+async fn get_readiness(state: web::Data<AppState>) -> HttpResponse {
+    let report = check_readiness(&state).await;
+
+    if report.ready {
+        HttpResponse::Ok().json(report)
+    } else {
+        HttpResponse::ServiceUnavailable().json(report)
+    }
+}
+
+pub fn get_health_scope() -> Scope {
+    web::scope("/health")
+        .service(get_health)
+        .service(get_liveness)
+        .service(get_readiness)
+}
@@ -0,0 +1,22 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::{error::AppError, metrics, AppState};
+
+/// Served on the separate metrics port, so that it is not reachable through the API.
+#[utoipa::path(
+    tag = "metrics",
+    security(()),
+    responses(
+        (status = 200, description = "The metrics in the Prometheus text format", body = String, content_type = "text/plain"),
+    )
+)]
+#[get("/metrics")]
+async fn metrics_handler(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    metrics::refresh(&state).await;
+    let body = metrics::metrics()
+        .encode()
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok()
+        .content_type(prometheus::TEXT_FORMAT)
+        .body(body))
+}
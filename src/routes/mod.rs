@@ -1,11 +1,39 @@
 mod attestation_requests;
 mod challenge;
 mod credentials;
+mod delegation;
+mod did;
 mod endpoints;
+mod evidence;
+mod health;
+mod metrics;
+mod openapi;
 mod well_known_did_config;
 
-pub use attestation_requests::get_attestation_request_scope;
-pub use challenge::get_challenge_scope;
-pub use credentials::get_credential_scope;
-pub use endpoints::get_endpoint_scope;
-pub use well_known_did_config::well_known_did_config_handler;
+#[cfg(test)]
+mod tests;
+
+use actix_web::web;
+use actix_web_httpauth::middleware::HttpAuthentication;
+
+use crate::auth::jwt_validator;
+
+pub use metrics::metrics_handler;
+
+/// Registers the services of the REST API. The scopes which require a login are wrapped with the
+/// authentication middleware.
+pub fn configure_api(config: &mut web::ServiceConfig, max_evidence_size: usize) {
+    let auth = HttpAuthentication::bearer(jwt_validator);
+
+    config
+        .service(attestation_requests::get_attestation_request_scope().wrap(auth.clone()))
+        .service(challenge::get_challenge_scope().wrap(auth.clone()))
+        .service(credentials::get_credential_scope().wrap(auth.clone()))
+        .service(delegation::get_delegation_scope().wrap(auth.clone()))
+        .service(did::get_did_scope().wrap(auth.clone()))
+        .service(evidence::get_evidence_scope(max_evidence_size).wrap(auth))
+        .service(endpoints::get_endpoint_scope())
+        .service(health::get_health_scope())
+        .service(openapi::get_openapi_service())
+        .service(well_known_did_config::well_known_did_config_handler);
+}
@@ -0,0 +1,96 @@
+use attester_api::routes;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use super::{
+    attestation_requests, challenge, credentials, delegation, did, endpoints, evidence, health,
+    metrics, well_known_did_config,
+};
+
+/// OpenAPI document of the REST API. New handlers have to be listed here, the drift test checks
+/// that every documented route is served. The metrics are documented as well, although they are
+/// served on a separate port.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Attester Service"),
+    paths(
+        attestation_requests::get_attestation,
+        attestation_requests::get_attestations,
+        attestation_requests::delete_attestation,
+        attestation_requests::post_attestation,
+        attestation_requests::approve_attestation,
+        attestation_requests::mark_approve_attestation_request,
+        attestation_requests::reject_attestation,
+        attestation_requests::request_changes,
+        attestation_requests::update_credential,
+        attestation_requests::get_revisions,
+        attestation_requests::get_attestation_comments,
+        attestation_requests::post_attestation_comment,
+        attestation_requests::assign_attestation_reviewer,
+        attestation_requests::get_attestation_reviewers,
+        attestation_requests::get_attestation_reviews,
+        attestation_requests::revoke_attestation,
+        attestation_requests::request_revocation,
+        attestation_requests::get_attestation_revocation_requests,
+        attestation_requests::get_attestation_kpis,
+        challenge::challenge_handler,
+        challenge::challenge_response_handler,
+        credentials::send_terms,
+        credentials::request_attestation,
+        credentials::confirm_payment,
+        credentials::deliver_attestation,
+        credentials::request_credential,
+        credentials::submit_credential,
+        credentials::get_verification,
+        delegation::get_hierarchy,
+        delegation::post_hierarchy,
+        delegation::post_delegation_payload,
+        delegation::post_delegation,
+        delegation::revoke_delegation_node,
+        did::get_did,
+        did::set_attestation_key,
+        did::set_delegation_key,
+        did::add_key_agreement_key,
+        did::remove_key_agreement_key,
+        did::add_service_endpoint,
+        did::remove_service_endpoint,
+        endpoints::get_endpoints,
+        evidence::upload_evidence,
+        evidence::get_attestation_evidences,
+        evidence::download_evidence,
+        health::get_health,
+        health::get_liveness,
+        health::get_readiness,
+        metrics::metrics_handler,
+        well_known_did_config::well_known_did_config_handler,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+)]
+pub struct ApiDoc;
+
+/// The JWT issued by OpenDID.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Serves the OpenAPI document at `/api/v1/openapi.json` and the Swagger UI at `/api/v1/docs/`.
+pub fn get_openapi_service() -> SwaggerUi {
+    SwaggerUi::new("/api/v1/docs/{_:.*}").url(routes::OPENAPI, ApiDoc::openapi())
+}
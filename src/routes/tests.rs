@@ -0,0 +1,1058 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use actix_http::Request;
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse},
+    http::{header, Method, StatusCode},
+    test::{call_and_read_body_json, init_service, read_body_json, TestRequest},
+    web, App, HttpResponse,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use attester_api::routes;
+use base58::ToBase58;
+use hmac::{Hmac, Mac};
+use jwt::SignWithKey;
+use serde::Serialize;
+use sha2::Sha256;
+use sodiumoxide::crypto::box_;
+use sqlx::PgPool;
+use subxt::{
+    ext::sp_core::{sr25519::Pair, Pair as _, H256},
+    tx::PairSigner,
+    utils::AccountId32,
+};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use super::openapi::{get_openapi_service, ApiDoc};
+use super::{
+    attestation_requests::get_attestation_request_scope, challenge::get_challenge_scope,
+    configure_api, credentials::get_credential_scope, evidence::get_evidence_scope,
+    metrics_handler,
+};
+use crate::{
+    auth::jwt_validator,
+    configuration::{CTypeConfig, SessionConfig},
+    database::{
+        dto::{
+            AttestationResponse, AttestationState, CTypeRequest, ChallengeData, ChallengeResponse,
+            Credential, DelegationPermissions, EncryptedMessage, ErrorMessageContent,
+            ErrorResponse, Message, MessageContent, RequestAttestationMessageContent,
+            SubmitAttestationMessageContent, SubmitTermsMessageContent, TxState,
+        },
+        querys::get_attestation_request_by_id,
+    },
+    json_config,
+    kilt::{get_did_uri, Chain, MockChain, OnChainDelegationNode, WellKnownDidConfig},
+    path_config, query_config, AppState,
+};
+
+/// Header of the responses of the default service, which answers requests that match no route.
+const UNMATCHED_ROUTE: &str = "x-unmatched-route";
+
+/// Replaces the path parameters with a value which every parameter accepts.
+fn fill_path_parameters(path: &str) -> String {
+    let mut filled = String::with_capacity(path.len());
+    let mut in_parameter = false;
+    for c in path.chars() {
+        match c {
+            '{' => {
+                in_parameter = true;
+                filled.push_str(&Uuid::nil().to_string());
+            }
+            '}' => in_parameter = false,
+            c if !in_parameter => filled.push(c),
+            _ => {}
+        }
+    }
+    filled
+}
+
+/// Returns the method and path of every operation of the OpenAPI document.
+fn documented_routes() -> BTreeSet<(String, String)> {
+    let openapi = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut routes = BTreeSet::new();
+    for (path, item) in openapi["paths"].as_object().unwrap() {
+        for method in item.as_object().unwrap().keys() {
+            if method.parse::<Method>().is_ok() {
+                routes.insert((method.to_uppercase(), path.clone()));
+            }
+        }
+    }
+    routes
+}
+
+#[sqlx::test]
+async fn test_openapi_routes_are_served(db_executor: PgPool) {
+    // Arrange: Register the services of the server, including the metrics which are served on a
+    // separate port, and mark the requests which are not routed to any of them.
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(
+        App::new()
+            .app_data(json_config())
+            .app_data(path_config())
+            .app_data(query_config())
+            .app_data(web::Data::new(state))
+            .configure(|config| configure_api(config, 0))
+            .service(metrics_handler)
+            .default_service(web::to(|| async {
+                HttpResponse::NotFound()
+                    .insert_header((UNMATCHED_ROUTE, "true"))
+                    .finish()
+            })),
+    )
+    .await;
+    let documented = documented_routes();
+
+    // Act: Send a request to every documented route as an admin.
+    let mut not_served = Vec::new();
+    for (method, path) in documented.iter() {
+        let response = TestRequest::default()
+            .method(method.parse().unwrap())
+            .uri(&fill_path_parameters(path))
+            .insert_header(authorization(ADMIN, true))
+            .send_request(&app)
+            .await;
+        if response.headers().contains_key(UNMATCHED_ROUTE) {
+            not_served.push((method, path));
+        }
+    }
+
+    // Assert
+    assert!(!documented.is_empty(), "No routes are documented");
+    assert!(
+        not_served.is_empty(),
+        "Documented routes which are not served: {not_served:?}"
+    );
+}
+
+#[test]
+fn test_openapi_contains_schemas() {
+    // Arrange
+    let openapi = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+    // Act
+    let schemas = openapi["components"]["schemas"].as_object().unwrap();
+
+    // Assert
+    for schema in [
+        "Credential",
+        "AttestationResponse",
+        "ChallengeData",
+        "EncryptedMessage",
+        "ErrorResponse",
+    ] {
+        assert!(schemas.contains_key(schema), "Schema {schema} is missing");
+    }
+    let parameters = &openapi["paths"]["/api/v1/attestation_request"]["get"]["parameters"];
+    let names: Vec<_> = parameters
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["range", "sort", "filter"]);
+}
+
+#[actix_web::test]
+async fn test_openapi_is_served() {
+    // Arrange
+    let app = init_service(App::new().service(get_openapi_service())).await;
+
+    // Act
+    let spec = TestRequest::get()
+        .uri("/api/v1/openapi.json")
+        .send_request(&app)
+        .await;
+    let docs = TestRequest::get()
+        .uri("/api/v1/docs/")
+        .send_request(&app)
+        .await;
+
+    // Assert
+    assert_eq!(spec.status(), StatusCode::OK);
+    let spec: serde_json::Value = read_body_json(spec).await;
+    assert_eq!(spec, serde_json::to_value(ApiDoc::openapi()).unwrap());
+    assert_eq!(docs.status(), StatusCode::OK);
+}
+
+/// Claimer of the attestation request of [`get_credential`].
+const CLAIMER: &str = "did:kilt:4qBmSXvzSYCkTnCyqtE62KhNLrvUKvtxmkwJNQrRdMztpT1r";
+const ADMIN: &str = "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH";
+const JWT_SECRET: &str = "secret";
+
+fn get_credential() -> Credential {
+    serde_json::from_value(serde_json::json!({
+        "claim": {
+            "cTypeHash": "0x3291bb126e33b4862d421bfaa1d2f272e6cdfc4f96658988fbcffea8914bd9ac",
+            "contents": {
+                "Email": "hello@kilt.io"
+            },
+            "owner": CLAIMER
+        },
+        "claimHashes": [
+            "0x22375cc1baba0ef8d7da11bd1b158a98034161ff74fe866fe192085fc0f03ffc",
+            "0x904c89de0da7ab1a53751ed8ff982ada61dfa1ba7ef2addff5d1260739543db5"
+        ],
+        "claimNonceMap": {
+            "0xb63271bb51c47bcca99b0f03c8cade56263c9eb31c9858ffa0a34a89f8523be4": "5f25a0d1-b68f-4e06-a003-26c391935540",
+            "0xf2aba36566e0f998683a172effe6443adb9cdd52f63ef264faeab13e4e41f1b7": "c57e9c72-fa8a-4e4f-b60f-a20234317bda"
+        },
+        "rootHash": "0x63acce746b1b9063d20e5de1aa39f02ff9123878d920fafcba0fc30f5ccb00ec",
+        "claimerSignature": {
+            "keyId": "did:kilt:4siDmerNEBREZJsFoLM95x6cxEho73bCWKEDAXrKdou4a3mH#0x78579576fa15684e5d868c9e123d62d471f1a95d8f9fc8032179d3735069784d",
+            "signature": "0x6243baecdfa9c752161f501597bafbb0242db1174bb8362c18d6e51bdbbdf041997fb736a07dcf56cb023687c4cc044ffba39e0dfcf01b7caa00f0f8b4fbbd81"
+        },
+        "legitimations": []
+    }))
+    .unwrap()
+}
+
+fn get_app_state(db_executor: PgPool) -> (AppState, Arc<MockChain>) {
+    let pair = |seed: &str| Arc::new(PairSigner::new(Pair::from_string(seed, None).unwrap()));
+    let signer = pair("//Alice");
+    let attester_did = AccountId32(signer.signer().public().0);
+    let chain = Arc::new(MockChain::new(&attester_did));
+    let key_uri = format!("{}#0x{}", get_did_uri(&attester_did), "01".repeat(32));
+
+    let state = AppState {
+        payer: pair("//Bob"),
+        signer: signer.clone(),
+        authentication_signer: signer.clone(),
+        delegation_signer: signer,
+        app_name: "attester".to_string(),
+        jwt_secret: JWT_SECRET.to_string(),
+        db_executor: Arc::new(db_executor),
+        attester_did,
+        well_known_did_config: WellKnownDidConfig {
+            context: String::new(),
+            linked_dids: vec![],
+        },
+        session: SessionConfig {
+            session_key: String::new(),
+            key_uri,
+            nacl_public_key: String::new(),
+            nacl_secret_key: String::new(),
+        },
+        encryption_key: box_::gen_keypair().1,
+        auth_url: String::new(),
+        endpoint: String::new(),
+        ctypes: HashMap::new(),
+        evidence_store: None,
+        min_payer_balance: None,
+        chain: chain.clone(),
+    };
+    (state, chain)
+}
+
+fn get_app(
+    state: AppState,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let auth = HttpAuthentication::bearer(jwt_validator);
+    App::new()
+        .app_data(json_config())
+        .app_data(path_config())
+        .app_data(query_config())
+        .app_data(web::Data::new(state))
+        .service(get_attestation_request_scope().wrap(auth.clone()))
+        .service(get_challenge_scope().wrap(auth.clone()))
+        .service(get_credential_scope().wrap(auth.clone()))
+        .service(get_evidence_scope(1024).wrap(auth))
+}
+
+/// Returns the bearer authorization header of a token which is issued by the auth service.
+fn authorization(user_id: &str, is_admin: bool) -> (header::HeaderName, String) {
+    let roles = if is_admin {
+        serde_json::json!({ "admin": true })
+    } else {
+        serde_json::json!({})
+    };
+    let payload = serde_json::json!({
+        "sub": user_id,
+        "w3n": "",
+        "exp": 0,
+        "iat": 0,
+        "iss": "",
+        "aud": "",
+        "pro": roles,
+        "nonce": "",
+    });
+    let key: Hmac<Sha256> = Hmac::new_from_slice(JWT_SECRET.as_bytes()).unwrap();
+    let token = payload.sign_with_key(&key).unwrap();
+    (header::AUTHORIZATION, format!("Bearer {token}"))
+}
+
+/// Wallet of the claimer, which communicates through encrypted Credential API messages.
+struct Wallet {
+    key_uri: String,
+    secret_key: box_::SecretKey,
+}
+
+impl Wallet {
+    /// Creates a wallet with a light DID, whose encryption key is part of the DID itself.
+    fn with_light_did() -> Self {
+        let (public_key, secret_key) = box_::gen_keypair();
+        let key = |key: &str| serde_cbor::Value::Text(key.to_string());
+        let details = serde_cbor::Value::Map(BTreeMap::from([(
+            key("e"),
+            serde_cbor::Value::Map(BTreeMap::from([
+                (
+                    key("publicKey"),
+                    serde_cbor::Value::Bytes(public_key.0.to_vec()),
+                ),
+                (key("type"), key("x25519")),
+            ])),
+        )]));
+        let mut encoded = vec![0];
+        encoded.extend(serde_cbor::to_vec(&details).unwrap());
+        let address = CLAIMER.trim_start_matches("did:kilt:");
+        Wallet {
+            key_uri: format!(
+                "did:kilt:light:00{address}:z{}#encryption",
+                encoded.to_base58()
+            ),
+            secret_key,
+        }
+    }
+
+    /// Creates a wallet with a full DID, whose encryption key is resolved on chain.
+    fn with_full_did(chain: &MockChain) -> Self {
+        let (public_key, secret_key) = box_::gen_keypair();
+        let key_uri = format!("{CLAIMER}#0x{}", "11".repeat(32));
+        chain.add_encryption_key(&key_uri, public_key);
+        Wallet {
+            key_uri,
+            secret_key,
+        }
+    }
+
+    fn encrypt<T: MessageContent + Serialize>(
+        &self,
+        content: T,
+        state: &AppState,
+    ) -> EncryptedMessage {
+        let msg = Message::new(content, CLAIMER, &state.session.key_uri, None);
+        let nonce = box_::gen_nonce();
+        EncryptedMessage {
+            cipher_text: box_::seal(
+                &serde_json::to_vec(&msg).unwrap(),
+                &nonce,
+                &state.encryption_key.public_key(),
+                &self.secret_key,
+            ),
+            nonce,
+            sender_key_uri: self.key_uri.clone(),
+            receiver_key_uri: state.session.key_uri.clone(),
+        }
+    }
+
+    fn decrypt(&self, message: &EncryptedMessage, state: &AppState) -> Message<serde_json::Value> {
+        assert_eq!(message.receiver_key_uri, self.key_uri);
+        let bytes = box_::open(
+            &message.cipher_text,
+            &message.nonce,
+            &state.encryption_key.public_key(),
+            &self.secret_key,
+        )
+        .expect("Message should be encrypted for the wallet");
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    /// Opens a session with the attester by answering the challenge.
+    async fn open_session<S, B>(&self, app: &S, state: &AppState) -> Uuid
+    where
+        S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+        B: MessageBody,
+    {
+        let challenge: ChallengeData = call_and_read_body_json(
+            app,
+            TestRequest::get()
+                .uri(routes::CHALLENGE)
+                .insert_header(authorization(CLAIMER, false))
+                .to_request(),
+        )
+        .await;
+        let nonce = box_::gen_nonce();
+        let response = ChallengeResponse {
+            encryption_key_uri: self.key_uri.clone(),
+            encrypted_challenge: box_::seal(
+                &challenge.challenge,
+                &nonce,
+                &state.encryption_key.public_key(),
+                &self.secret_key,
+            ),
+            nonce,
+        };
+        call_and_read_body_json(
+            app,
+            TestRequest::post()
+                .uri(routes::CHALLENGE)
+                .insert_header(authorization(CLAIMER, false))
+                .set_json(response)
+                .to_request(),
+        )
+        .await
+    }
+}
+
+async fn create_attestation_request<S, B>(app: &S) -> AttestationResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    create_attestation_request_for(app, &get_credential()).await
+}
+
+async fn create_attestation_request_for<S, B>(
+    app: &S,
+    credential: &Credential,
+) -> AttestationResponse
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    call_and_read_body_json(
+        app,
+        TestRequest::post()
+            .uri(routes::ATTESTATION_REQUEST)
+            .insert_header(authorization(CLAIMER, false))
+            .set_json(credential)
+            .to_request(),
+    )
+    .await
+}
+
+async fn put_as_admin<S, B>(app: &S, uri: &str) -> StatusCode
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    TestRequest::put()
+        .uri(uri)
+        .insert_header(authorization(ADMIN, true))
+        .send_request(app)
+        .await
+        .status()
+}
+
+/// Waits until the transaction of the attestation request, which is submitted in the
+/// background, is no longer in flight.
+async fn wait_for_tx(attestation_id: &Uuid, db_executor: &PgPool) -> AttestationResponse {
+    for _ in 0..100 {
+        let attestation = get_attestation_request_by_id(attestation_id, db_executor)
+            .await
+            .unwrap();
+        if attestation.tx_state != Some(TxState::InFlight) {
+            return attestation;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Transaction is still in flight");
+}
+
+#[sqlx::test]
+async fn test_approve_deliver_and_revoke_attestation(db_executor: PgPool) {
+    // Arrange: Create an attestation request as the claimer.
+    let (state, chain) = get_app_state(db_executor.clone());
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let credential = get_credential();
+    let uri = format!("{}/{}", routes::ATTESTATION_REQUEST, attestation.id);
+
+    // Act: Approve the attestation request as an admin.
+    let status = put_as_admin(&app, &format!("{uri}/approve")).await;
+
+    // Assert: Check that the claim is anchored on chain and the request is approved.
+    assert_eq!(status, StatusCode::OK);
+    let approved = wait_for_tx(&attestation.id, &db_executor).await;
+    assert_eq!(approved.status, AttestationState::Approved);
+    assert_eq!(approved.tx_state, Some(TxState::Succeeded));
+    let claim = chain
+        .get_claim(&credential.root_hash)
+        .expect("Claim should be anchored");
+    assert!(!claim.revoked);
+    assert_eq!(chain.get_next_tx_counter().await.unwrap(), 2);
+
+    // Act: Deliver the queued attestation to the wallet of the claimer.
+    let wallet = Wallet::with_light_did();
+    let session_id = wallet.open_session(&app, &state).await;
+    let response: EncryptedMessage = call_and_read_body_json(
+        &app,
+        TestRequest::post()
+            .uri(&format!(
+                "{}/attestation/{session_id}/{}",
+                routes::CREDENTIAL,
+                attestation.id
+            ))
+            .insert_header(authorization(CLAIMER, false))
+            .to_request(),
+    )
+    .await;
+
+    // Assert: Check that the wallet receives the attestation.
+    let message = wallet
+        .decrypt(&response, &state)
+        .into_content::<SubmitAttestationMessageContent>()
+        .unwrap();
+    assert_eq!(
+        message.body.content.attestation.claim_hash,
+        credential.root_hash
+    );
+    assert!(!message.body.content.attestation.revoked);
+
+    // Act: Revoke the attestation as an admin.
+    let status = put_as_admin(&app, &format!("{uri}/revoke")).await;
+
+    // Assert: Check that the claim is revoked on chain and in the database.
+    assert_eq!(status, StatusCode::OK);
+    let revoked = wait_for_tx(&attestation.id, &db_executor).await;
+    assert_eq!(revoked.status, AttestationState::Revoked);
+    assert!(chain.get_claim(&credential.root_hash).unwrap().revoked);
+    assert_eq!(chain.get_next_tx_counter().await.unwrap(), 3);
+}
+
+#[sqlx::test]
+async fn test_approve_attestation_chain_failure(db_executor: PgPool) {
+    // Arrange: Create an attestation request and let the chain reject all transactions.
+    let (state, chain) = get_app_state(db_executor.clone());
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request(&app).await;
+    chain.set_failing(true);
+    let uri = format!("{}/{}/approve", routes::ATTESTATION_REQUEST, attestation.id);
+
+    // Act: Approve the attestation request as an admin.
+    let status = put_as_admin(&app, &uri).await;
+
+    // Assert: Check that the failed transaction is recorded and nothing is anchored.
+    assert_eq!(status, StatusCode::OK);
+    let failed = wait_for_tx(&attestation.id, &db_executor).await;
+    assert_eq!(failed.tx_state, Some(TxState::Failed));
+    assert!(!failed.approved);
+    assert!(chain.get_claim(&get_credential().root_hash).is_none());
+    assert_eq!(chain.get_next_tx_counter().await.unwrap(), 1);
+
+    // Act: Revoke the attestation which was never anchored.
+    let status = put_as_admin(
+        &app,
+        &format!("{}/{}/revoke", routes::ATTESTATION_REQUEST, attestation.id),
+    )
+    .await;
+
+    // Assert: Check that the revocation is rejected.
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test]
+async fn test_request_revocation_not_approved(db_executor: PgPool) {
+    // Arrange: Create an attestation request which is still pending.
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request(&app).await;
+
+    // Act: Request the revocation as the claimer.
+    let response = TestRequest::put()
+        .uri(&format!(
+            "{}/{}/request_revocation",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the request conflicts with the current state.
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "invalid_state_transition");
+    assert_eq!(error.details.unwrap()["currentState"], "Pending");
+}
+
+#[sqlx::test]
+async fn test_request_revocation_as_admin(db_executor: PgPool) {
+    // Arrange
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request(&app).await;
+
+    // Act: Request the revocation as an admin who is not the claimer.
+    let response = TestRequest::put()
+        .uri(&format!(
+            "{}/{}/request_revocation",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ))
+        .insert_header(authorization(ADMIN, true))
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that only the claimer can request the revocation.
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test]
+async fn test_credential_api_attestation_flow(db_executor: PgPool) {
+    // Arrange: Create an attestation request which is approved without anchoring it.
+    let (state, chain) = get_app_state(db_executor.clone());
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let credential = get_credential();
+    let status = put_as_admin(
+        &app,
+        &format!(
+            "{}/{}/mark_approve",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let wallet = Wallet::with_light_did();
+    let session_id = wallet.open_session(&app, &state).await;
+
+    // Act: Request the terms for the attestation.
+    let response: EncryptedMessage = call_and_read_body_json(
+        &app,
+        TestRequest::post()
+            .uri(&format!(
+                "{}/terms/{session_id}/{}",
+                routes::CREDENTIAL,
+                attestation.id
+            ))
+            .insert_header(authorization(CLAIMER, false))
+            .to_request(),
+    )
+    .await;
+
+    // Assert: Check that the terms contain the claim.
+    let terms = wallet
+        .decrypt(&response, &state)
+        .into_content::<SubmitTermsMessageContent>()
+        .unwrap();
+    assert_eq!(terms.body.content.claim, credential.claim);
+    assert!(terms.body.content.quote.is_none());
+
+    // Act: Request the attestation from a full DID of the claimer.
+    let full_did_wallet = Wallet::with_full_did(&chain);
+    let request = full_did_wallet.encrypt(
+        RequestAttestationMessageContent {
+            credential: credential.clone(),
+            quote: None,
+        },
+        &state,
+    );
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{session_id}/{}",
+            routes::CREDENTIAL,
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .set_json(request)
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the claim is anchored and the attestation is sent to the wallet.
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: EncryptedMessage = read_body_json(response).await;
+    let message = full_did_wallet
+        .decrypt(&response, &state)
+        .into_content::<SubmitAttestationMessageContent>()
+        .unwrap();
+    assert_eq!(
+        message.body.content.attestation.claim_hash,
+        credential.root_hash
+    );
+    assert_eq!(
+        message.body.content.attestation.owner,
+        get_did_uri(&state.attester_did)
+    );
+    assert!(chain.get_claim(&credential.root_hash).is_some());
+    let approved = get_attestation_request_by_id(&attestation.id, &db_executor)
+        .await
+        .unwrap();
+    assert!(approved.approved);
+    assert_eq!(approved.status, AttestationState::Approved);
+}
+
+#[sqlx::test]
+async fn test_credential_api_attestation_other_credential(db_executor: PgPool) {
+    // Arrange: Create an attestation request which is approved without anchoring it.
+    let (state, chain) = get_app_state(db_executor.clone());
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let status = put_as_admin(
+        &app,
+        &format!(
+            "{}/{}/mark_approve",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let wallet = Wallet::with_light_did();
+    let session_id = wallet.open_session(&app, &state).await;
+
+    // Act: Request the attestation of a credential which was not reviewed.
+    let mut credential = get_credential();
+    credential.root_hash = format!("0x{}", "33".repeat(32));
+    let request = wallet.encrypt(
+        RequestAttestationMessageContent {
+            credential: credential.clone(),
+            quote: None,
+        },
+        &state,
+    );
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{session_id}/{}",
+            routes::CREDENTIAL,
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .set_json(request)
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that nothing is anchored for the other credential.
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert!(chain.get_claim(&credential.root_hash).is_none());
+    let attestation = get_attestation_request_by_id(&attestation.id, &db_executor)
+        .await
+        .unwrap();
+    assert_eq!(attestation.status, AttestationState::MarkedApproved);
+}
+
+#[sqlx::test]
+async fn test_credential_api_attestation_other_ctype(db_executor: PgPool) {
+    // Arrange: Create an attestation request which is approved without anchoring it.
+    let (state, chain) = get_app_state(db_executor.clone());
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let status = put_as_admin(
+        &app,
+        &format!(
+            "{}/{}/mark_approve",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let wallet = Wallet::with_light_did();
+    let session_id = wallet.open_session(&app, &state).await;
+
+    // Act: Request the attestation of the reviewed root hash for another CType.
+    let mut credential = get_credential();
+    credential.claim.ctype_hash = format!("0x{}", "44".repeat(32));
+    let request = wallet.encrypt(
+        RequestAttestationMessageContent {
+            credential: credential.clone(),
+            quote: None,
+        },
+        &state,
+    );
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{session_id}/{}",
+            routes::CREDENTIAL,
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .set_json(request)
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the credential is rejected and nothing is anchored.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "invalid_credential");
+    assert!(chain.get_claim(&credential.root_hash).is_none());
+    let attestation = get_attestation_request_by_id(&attestation.id, &db_executor)
+        .await
+        .unwrap();
+    assert_eq!(attestation.status, AttestationState::MarkedApproved);
+}
+
+#[sqlx::test]
+async fn test_credential_api_malformed_sender(db_executor: PgPool) {
+    // Arrange: Encrypt a request for an attestation request from a malformed light DID.
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let mut request = Wallet::with_light_did().encrypt(
+        RequestAttestationMessageContent {
+            credential: get_credential(),
+            quote: None,
+        },
+        &state,
+    );
+    request.sender_key_uri = "did:kilt:light:malformed#encryption".to_string();
+
+    // Act
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{}/{}",
+            routes::CREDENTIAL,
+            Uuid::new_v4(),
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .set_json(request)
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the malformed input is reported as a client error.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "light_did_error");
+}
+
+#[sqlx::test]
+async fn test_credential_api_wallet_error(db_executor: PgPool) {
+    // Arrange: Let the wallet of the claimer answer the terms with an error message.
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let request = Wallet::with_light_did().encrypt(
+        ErrorMessageContent {
+            name: Some("Error".to_string()),
+            message: Some("Unable to process the terms".to_string()),
+        },
+        &state,
+    );
+
+    // Act
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{}/{}",
+            routes::CREDENTIAL,
+            Uuid::new_v4(),
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .set_json(request)
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the error is reported as such and not as declined terms.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "invalid_message");
+    assert_eq!(
+        error.message,
+        "Message error: Wallet of the claimer reported an error: Unable to process the terms"
+    );
+    let attestation = get_attestation_request_by_id(&attestation.id, &state.db_executor)
+        .await
+        .unwrap();
+    assert_eq!(attestation.status, AttestationState::Pending);
+}
+
+#[sqlx::test]
+async fn test_credential_api_tampered_message(db_executor: PgPool) {
+    // Arrange: Encrypt a request for an attestation request and tamper with the cipher text.
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(get_app(state.clone())).await;
+    let attestation = create_attestation_request(&app).await;
+    let mut request = Wallet::with_light_did().encrypt(
+        RequestAttestationMessageContent {
+            credential: get_credential(),
+            quote: None,
+        },
+        &state,
+    );
+    request.cipher_text[0] ^= 1;
+
+    // Act
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{}/{}",
+            routes::CREDENTIAL,
+            Uuid::new_v4(),
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .set_json(request)
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the message which can not be decrypted is reported as a client error.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "bad_request");
+}
+
+#[sqlx::test]
+async fn test_upload_empty_evidence(db_executor: PgPool) {
+    // Arrange
+    let (state, _) = get_app_state(db_executor);
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request(&app).await;
+
+    // Act: Upload an evidence without content.
+    let response = TestRequest::post()
+        .uri(&format!(
+            "{}/{}?fileName=empty.pdf",
+            routes::EVIDENCE,
+            attestation.id
+        ))
+        .insert_header(authorization(CLAIMER, false))
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the invalid upload is reported as a client error.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "bad_request");
+}
+
+#[sqlx::test]
+async fn test_approve_attestation_missing_legitimation(db_executor: PgPool) {
+    // Arrange: Require a legitimation for the CType of the attestation request.
+    let (mut state, chain) = get_app_state(db_executor.clone());
+    let credential = get_credential();
+    state.ctypes.insert(
+        credential.claim.ctype_hash.clone(),
+        CTypeConfig {
+            legitimations: vec![CTypeRequest {
+                ctype_hash: format!("0x{}", "22".repeat(32)),
+                trusted_attesters: None,
+                required_properties: None,
+            }],
+            ..Default::default()
+        },
+    );
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request(&app).await;
+
+    // Act: Approve the attestation request as an admin.
+    let response = TestRequest::put()
+        .uri(&format!(
+            "{}/{}/approve",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ))
+        .insert_header(authorization(ADMIN, true))
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the approval is rejected before a transaction is submitted.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "invalid_credential");
+    assert!(chain.get_claim(&credential.root_hash).is_none());
+    let attestation = get_attestation_request_by_id(&attestation.id, &db_executor)
+        .await
+        .unwrap();
+    assert!(!attestation.approved);
+    assert_ne!(attestation.tx_state, Some(TxState::InFlight));
+}
+
+/// Returns the credential of [`get_credential`] under a delegation node of the attester, whose
+/// hierarchy is created for `hierarchy_ctype_hash` on the in-memory chain.
+fn get_delegated_credential(
+    state: &mut AppState,
+    chain: &MockChain,
+    hierarchy_ctype_hash: H256,
+) -> Credential {
+    let delegation_id = H256::repeat_byte(6);
+    let mut credential = get_credential();
+    credential.delegation_id = Some(format!("{:?}", delegation_id));
+    credential.root_hash =
+        "0x8a4466c254c69a566d6ff9bab1935a82b75f25f77d2c74d6a5ad146495ddd47e".to_string();
+    state.ctypes.insert(
+        credential.claim.ctype_hash.clone(),
+        CTypeConfig {
+            delegation_id: credential.delegation_id.clone(),
+            ..Default::default()
+        },
+    );
+    chain.add_delegation_hierarchy(delegation_id, hierarchy_ctype_hash);
+    chain.add_delegation_node(
+        delegation_id,
+        OnChainDelegationNode {
+            hierarchy_root_id: delegation_id,
+            parent: None,
+            owner: state.attester_did.clone(),
+            permissions: u32::from(DelegationPermissions {
+                attest: true,
+                delegate: true,
+            }),
+            revoked: false,
+        },
+    );
+    credential
+}
+
+#[sqlx::test]
+async fn test_approve_delegated_attestation(db_executor: PgPool) {
+    // Arrange: Configure a delegation node of the attester for the CType of the credential.
+    let (mut state, chain) = get_app_state(db_executor.clone());
+    let ctype_hash = H256::from_slice(
+        &hex::decode(get_credential().claim.ctype_hash.trim_start_matches("0x")).unwrap(),
+    );
+    let credential = get_delegated_credential(&mut state, &chain, ctype_hash);
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request_for(&app, &credential).await;
+
+    // Act: Approve the attestation request as an admin.
+    let status = put_as_admin(
+        &app,
+        &format!("{}/{}/approve", routes::ATTESTATION_REQUEST, attestation.id),
+    )
+    .await;
+
+    // Assert: Check that the claim is anchored under the delegation node.
+    assert_eq!(status, StatusCode::OK);
+    let approved = wait_for_tx(&attestation.id, &db_executor).await;
+    assert_eq!(approved.status, AttestationState::Approved);
+    let claim = chain
+        .get_claim(&credential.root_hash)
+        .expect("Claim should be anchored");
+    assert_eq!(claim.delegation_id, Some(H256::repeat_byte(6)));
+    assert_eq!(claim.ctype_hash, ctype_hash);
+}
+
+#[sqlx::test]
+async fn test_approve_delegated_attestation_other_ctype(db_executor: PgPool) {
+    // Arrange: Create the hierarchy of the delegation node for another CType.
+    let (mut state, chain) = get_app_state(db_executor.clone());
+    let credential = get_delegated_credential(&mut state, &chain, H256::repeat_byte(7));
+    let app = init_service(get_app(state)).await;
+    let attestation = create_attestation_request_for(&app, &credential).await;
+
+    // Act: Approve the attestation request as an admin.
+    let response = TestRequest::put()
+        .uri(&format!(
+            "{}/{}/approve",
+            routes::ATTESTATION_REQUEST,
+            attestation.id
+        ))
+        .insert_header(authorization(ADMIN, true))
+        .send_request(&app)
+        .await;
+
+    // Assert: Check that the approval is rejected before a transaction is submitted.
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = read_body_json(response).await;
+    assert_eq!(error.code, "delegation_error");
+    assert!(chain.get_claim(&credential.root_hash).is_none());
+    let attestation = get_attestation_request_by_id(&attestation.id, &db_executor)
+        .await
+        .unwrap();
+    assert_eq!(attestation.status, AttestationState::Pending);
+}
@@ -1,7 +1,14 @@
 use actix_web::{get, web, HttpResponse};
 
-use crate::{error::AppError, AppState};
+use crate::{error::AppError, kilt::WellKnownDidConfig, AppState};
 
+#[utoipa::path(
+    tag = "well_known",
+    security(()),
+    responses(
+        (status = 200, description = "The DID configuration which links the domain to the attester DID", body = WellKnownDidConfig),
+    )
+)]
 #[get("/.well-known/did-configuration.json")]
 async fn well_known_did_config_handler(
     app_state: web::Data<AppState>,
@@ -0,0 +1,90 @@
+//! Initialization of the structured logs. Every HTTP request gets a span with a request ID, which
+//! is propagated into the background tasks, so that a chain submission can be correlated with the
+//! request that started it. The spans are optionally exported to an OpenTelemetry collector.
+
+use std::future::Future;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use tracing_actix_web::RequestId;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+use crate::configuration::{LogFormat, LoggingConfig};
+
+const SERVICE_NAME: &str = "attester-backend";
+
+tokio::task_local! {
+    static REQUEST_ID: Option<RequestId>;
+}
+
+/// Runs the handling of a request with its request ID, so that error responses can include it.
+pub async fn with_request_id<F: Future>(request_id: Option<RequestId>, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID
+        .try_with(|request_id| request_id.map(|id| id.to_string()))
+        .ok()
+        .flatten()
+}
+
+/// Flushes the exported spans when it is dropped.
+pub struct TelemetryGuard {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Shutting down the span exporter failed: {}", e);
+            }
+        }
+    }
+}
+
+fn init_tracer_provider(endpoint: &str) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build())
+}
+
+/// Installs the global subscriber. Records of the `log` crate are forwarded to it as well.
+pub fn init(config: &LoggingConfig) -> anyhow::Result<TelemetryGuard> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let fmt_layer = match config.format {
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+    };
+
+    let tracer_provider = config
+        .otlp_endpoint
+        .as_deref()
+        .map(init_tracer_provider)
+        .transpose()?;
+
+    let otel_layer = tracer_provider
+        .as_ref()
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .try_init()?;
+
+    Ok(TelemetryGuard { tracer_provider })
+}
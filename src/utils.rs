@@ -1,10 +1,15 @@
 use actix_web::web::ReqData;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
     auth::User,
-    database::{dto::AttestationResponse, querys::get_attestation_request_by_id},
+    configuration::CTypeConfig,
+    database::{
+        dto::AttestationResponse,
+        querys::{get_assignments, get_attestation_request_by_id},
+    },
     error::AppError,
 };
 
@@ -37,6 +42,53 @@ pub async fn is_user_allowed_to_update_data(
     }
 }
 
+/// Evidences are only accessible to the claimer and the reviewers assigned to the request.
+pub async fn is_user_allowed_to_access_evidence(
+    user: &User,
+    attestation_id: &Uuid,
+    db_executor: &PgPool,
+) -> Result<bool, AppError> {
+    let attestation = get_attestation_request_by_id(attestation_id, db_executor).await?;
+    if attestation.claimer == user.id {
+        return Ok(true);
+    }
+
+    if !user.is_admin {
+        return Ok(false);
+    }
+
+    let assignments = get_assignments(attestation_id, db_executor).await?;
+    Ok(assignments
+        .iter()
+        .any(|assignment| assignment.reviewer == user.id))
+}
+
 pub fn is_user_admin(user: &User) -> bool {
     user.is_admin
 }
+
+/// Reviewers have to be admins and can not review their own requests.
+/// If reviewers are assigned to the request, only those are allowed to review it.
+pub async fn is_user_allowed_to_review(
+    user: &User,
+    attestation: &AttestationResponse,
+    db_executor: &PgPool,
+) -> Result<bool, AppError> {
+    if !user.is_admin || attestation.claimer == user.id {
+        return Ok(false);
+    }
+
+    let assignments = get_assignments(&attestation.id, db_executor).await?;
+    Ok(assignments.is_empty()
+        || assignments
+            .iter()
+            .any(|assignment| assignment.reviewer == user.id))
+}
+
+pub fn get_ctype_config(ctypes: &HashMap<String, CTypeConfig>, ctype_hash: &str) -> CTypeConfig {
+    ctypes
+        .iter()
+        .find(|(hash, _)| hash.eq_ignore_ascii_case(ctype_hash))
+        .map(|(_, config)| config.clone())
+        .unwrap_or_default()
+}